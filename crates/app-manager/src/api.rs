@@ -6,11 +6,17 @@ use utoipa::OpenApi;
 
 use crate::{
     config::Config,
-    server::{build::BuildManagerHandle, client::ApiManager, update::UpdateManagerHandle},
+    server::{
+        acme::AcmeChallenges, backend_controller::BackendController, build::BuildManagerHandle,
+        client::ApiManager, reboot::RebootManagerHandle, reload::ConfigReloader,
+        state::StateStorage, update::UpdateManagerHandle,
+    },
 };
 
 // Routes
+pub mod acme;
 pub mod manager;
+pub mod metrics;
 
 pub mod utils;
 
@@ -28,9 +34,19 @@ pub const PATH_PREFIX: &str = "/api/v1/";
         manager::get_latest_software,
         manager::get_system_info,
         manager::get_system_info_all,
+        manager::get_logs,
+        manager::get_backend_status,
+        manager::get_update_status,
+        manager::get_reboot_status,
         manager::post_request_build_software,
         manager::post_request_software_update,
         manager::post_request_restart_or_reset_backend,
+        manager::post_reload_config,
+        manager::post_schedule_reboot,
+        manager::post_reboot_now,
+        manager::post_cancel_reboot,
+        manager::post_acquire_reboot_lease,
+        manager::post_release_reboot_lease,
     ),
     components(schemas(
         model::DataEncryptionKey,
@@ -43,9 +59,28 @@ pub const PATH_PREFIX: &str = "/api/v1/";
         model::ResetDataQueryParam,
         model::SoftwareInfo,
         model::BuildInfo,
+        model::SystemInfoSectionsQueryParam,
+        model::LogsQueryParam,
         model::SystemInfoList,
         model::SystemInfo,
+        model::SystemMetrics,
+        model::DiskUsage,
+        model::MemoryUsage,
+        model::LoadAverage,
+        model::ProcessInfo,
         model::CommandOutput,
+        model::ConfigReloadReport,
+        model::BackendStatus,
+        model::UpdateStatus,
+        model::UpdateState,
+        model::UpdateStepInfo,
+        model::UpdateStep,
+        model::RebootStatus,
+        model::RebootReason,
+        model::RebootAttempt,
+        model::CancelRebootResult,
+        model::RebootLeaseQueryParam,
+        model::RebootLease,
     )),
     modifiers(&SecurityApiTokenDefault),
     info(
@@ -77,3 +112,23 @@ pub trait GetBuildManager {
 pub trait GetUpdateManager {
     fn update_manager(&self) -> &UpdateManagerHandle;
 }
+
+pub trait GetAcmeChallenges {
+    fn acme_challenges(&self) -> &AcmeChallenges;
+}
+
+pub trait GetConfigReloader {
+    fn config_reloader(&self) -> &ConfigReloader;
+}
+
+pub trait GetBackendController {
+    fn backend_controller(&self) -> &BackendController;
+}
+
+pub trait GetRebootManager {
+    fn reboot_manager(&self) -> &RebootManagerHandle;
+}
+
+pub trait GetStateStorage {
+    fn state_storage(&self) -> &StateStorage;
+}
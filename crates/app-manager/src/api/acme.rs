@@ -0,0 +1,21 @@
+//! ACME HTTP-01 challenge responses.
+//!
+//! These routes are not part of the manager API, so they are not
+//! included in the API docs.
+
+use axum::extract::Path;
+
+use super::{utils::StatusCode, GetAcmeChallenges};
+
+pub const PATH_GET_ACME_CHALLENGE: &str = "/.well-known/acme-challenge/:token";
+
+pub async fn get_acme_challenge<S: GetAcmeChallenges>(
+    Path(token): Path<String>,
+    state: S,
+) -> Result<String, StatusCode> {
+    state
+        .acme_challenges()
+        .key_authorization(&token)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)
+}
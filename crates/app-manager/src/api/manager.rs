@@ -1,17 +1,31 @@
 use std::net::SocketAddr;
 
 use axum::{
+    body::Body,
     extract::{ConnectInfo, Path, Query},
+    response::IntoResponse,
     Json,
 };
+use hyper::header;
 use manager_model::{
-    DataEncryptionKey, DownloadType, DownloadTypeQueryParam, RebootQueryParam, ResetDataQueryParam,
-    ServerNameText, SoftwareInfo, SoftwareOptionsQueryParam, SystemInfo, SystemInfoList,
+    BackendStatus, CancelRebootResult, ConfigReloadReport, DataEncryptionKey, DownloadType,
+    DownloadTypeQueryParam, LogsQueryParam, RebootLease, RebootLeaseQueryParam, RebootQueryParam,
+    RebootReason, RebootStatus, ResetDataQueryParam, ServerNameText, SoftwareInfo,
+    SoftwareOptionsQueryParam, SystemInfo, SystemInfoList, SystemInfoSectionsQueryParam,
+    UpdateStatus,
 };
-use tracing::info;
+use tracing::{info, warn};
 
-use super::{utils::StatusCode, GetApiManager, GetBuildManager, GetConfig, GetUpdateManager};
-use crate::server::{build::BuildDirCreator, info::SystemInfoGetter, update::UpdateDirCreator};
+use super::{
+    utils::StatusCode, GetApiManager, GetBackendController, GetBuildManager, GetConfig,
+    GetConfigReloader, GetRebootManager, GetStateStorage, GetUpdateManager,
+};
+use crate::server::{
+    build::BuildDirCreator,
+    info::{journal::JournalReader, SystemInfoError, SystemInfoGetter},
+    reboot_coordinator::RebootCoordinator,
+    update::UpdateDirCreator,
+};
 
 pub const PATH_GET_ENCRYPTION_KEY: &str = "/manager_api/encryption_key/:server";
 
@@ -33,6 +47,7 @@ pub async fn get_encryption_key<S: GetConfig>(
 ) -> Result<Json<DataEncryptionKey>, StatusCode> {
     if let Some(s) = state
         .config()
+        .reloadable()
         .encryption_keys()
         .iter()
         .find(|s| s.name == server.server)
@@ -228,6 +243,249 @@ pub async fn post_request_restart_or_reset_backend<S: GetConfig + GetUpdateManag
     Ok(())
 }
 
+pub const PATH_GET_BACKEND_STATUS: &str = "/manager_api/backend_status";
+
+/// Get backend process status.
+///
+/// Process details are available only when the backend is supervised by
+/// the manager or controlled using systemd.
+#[utoipa::path(
+    get,
+    path = "/manager_api/backend_status",
+    responses(
+        (status = 200, description = "Backend status", body = BackendStatus),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn get_backend_status<S: GetBackendController>(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Result<Json<BackendStatus>, StatusCode> {
+    info!("Get backend status received from {}.", client);
+
+    let status = state.backend_controller().backend_status().await?;
+    Ok(status.into())
+}
+
+pub const PATH_GET_UPDATE_STATUS: &str = "/manager_api/update_status";
+
+/// Get software update progress.
+///
+/// Returns steps of the current or previous software update or backend
+/// restart.
+#[utoipa::path(
+    get,
+    path = "/manager_api/update_status",
+    responses(
+        (status = 200, description = "Update status", body = UpdateStatus),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn get_update_status<S: GetUpdateManager>(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Json<UpdateStatus> {
+    info!("Get update status received from {}.", client);
+
+    state.update_manager().update_status().into()
+}
+
+pub const PATH_GET_REBOOT_STATUS: &str = "/manager_api/reboot_status";
+
+/// Get automatic reboot status.
+///
+/// Returns is reboot pending, the reason for the reboot, the next reboot
+/// check time and the result of the previous reboot attempt.
+#[utoipa::path(
+    get,
+    path = "/manager_api/reboot_status",
+    responses(
+        (status = 200, description = "Reboot status", body = RebootStatus),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn get_reboot_status<S: GetRebootManager>(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Json<RebootStatus> {
+    info!("Get reboot status received from {}.", client);
+
+    state.reboot_manager().status().await.into()
+}
+
+pub const PATH_POST_SCHEDULE_REBOOT: &str = "/manager_api/schedule_reboot";
+
+/// Reboot computer at the next reboot check.
+///
+/// Automatic reboots must be enabled from the config file.
+#[utoipa::path(
+    post,
+    path = "/manager_api/schedule_reboot",
+    responses(
+        (status = 200, description = "Reboot scheduled"),
+        (status = 409, description = "Automatic reboots are disabled."),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn post_schedule_reboot<S: GetConfig + GetRebootManager>(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Result<(), StatusCode> {
+    info!("Schedule reboot request received from {}", client);
+
+    if state.config().reloadable().reboot_if_needed().is_none() {
+        warn!("Reboot not scheduled because automatic reboots are disabled");
+        return Err(StatusCode::CONFLICT);
+    }
+
+    state
+        .reboot_manager()
+        .schedule_reboot(RebootReason::Scheduled)
+        .await;
+    Ok(())
+}
+
+pub const PATH_POST_REBOOT_NOW: &str = "/manager_api/reboot_now";
+
+/// Reboot computer now.
+///
+/// Reboot result is available from the reboot status after the manager
+/// restarts.
+#[utoipa::path(
+    post,
+    path = "/manager_api/reboot_now",
+    responses(
+        (status = 200, description = "Reboot request received"),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn post_reboot_now<S: GetRebootManager>(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Result<(), StatusCode> {
+    info!("Reboot now request received from {}", client);
+
+    state.reboot_manager().reboot_now().await?;
+    Ok(())
+}
+
+pub const PATH_POST_CANCEL_REBOOT: &str = "/manager_api/cancel_reboot";
+
+/// Cancel pending reboot.
+///
+/// The reboot required file created by the operating system is not
+/// removed, so the computer is still rebooted if that file exists.
+#[utoipa::path(
+    post,
+    path = "/manager_api/cancel_reboot",
+    responses(
+        (status = 200, description = "Cancel reboot result", body = CancelRebootResult),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn post_cancel_reboot<S: GetRebootManager>(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Json<CancelRebootResult> {
+    info!("Cancel reboot request received from {}", client);
+
+    let was_pending = state.reboot_manager().cancel_reboot().await;
+    if !was_pending {
+        info!("No pending reboot");
+    }
+
+    CancelRebootResult { was_pending }.into()
+}
+
+pub const PATH_POST_ACQUIRE_REBOOT_LEASE: &str = "/manager_api/acquire_reboot_lease";
+
+/// Request a reboot lease from the reboot coordinator.
+///
+/// The server can reboot if the lease is granted. Requesting an already
+/// granted lease renews it. Leases expire after the coordinator's lease
+/// timeout.
+#[utoipa::path(
+    post,
+    path = "/manager_api/acquire_reboot_lease",
+    params(RebootLeaseQueryParam),
+    responses(
+        (status = 200, description = "Reboot lease request handled", body = RebootLease),
+        (status = 500, description = "Internal server error. Reboot coordinator might not be configured."),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn post_acquire_reboot_lease<S: GetConfig + GetStateStorage>(
+    Query(lease): Query<RebootLeaseQueryParam>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Result<Json<RebootLease>, StatusCode> {
+    info!(
+        "Reboot lease request received from {}. Name: {}",
+        client, lease.name
+    );
+
+    let lease = RebootCoordinator::new(state.config(), state.state_storage())
+        .acquire(&lease.name)
+        .await?;
+    Ok(lease.into())
+}
+
+pub const PATH_POST_RELEASE_REBOOT_LEASE: &str = "/manager_api/release_reboot_lease";
+
+/// Release a reboot lease.
+#[utoipa::path(
+    post,
+    path = "/manager_api/release_reboot_lease",
+    params(RebootLeaseQueryParam),
+    responses(
+        (status = 200, description = "Reboot lease released"),
+        (status = 500, description = "Internal server error. Reboot coordinator might not be configured."),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn post_release_reboot_lease<S: GetConfig + GetStateStorage>(
+    Query(lease): Query<RebootLeaseQueryParam>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Result<(), StatusCode> {
+    info!(
+        "Reboot lease release received from {}. Name: {}",
+        client, lease.name
+    );
+
+    RebootCoordinator::new(state.config(), state.state_storage())
+        .release(&lease.name)
+        .await?;
+    Ok(())
+}
+
+pub const PATH_POST_RELOAD_CONFIG: &str = "/manager_api/reload_config";
+
+/// Reload config file without restarting the manager.
+///
+/// Only some config values can be changed without restart. Changed values
+/// which require restart are listed in the response.
+#[utoipa::path(
+    post,
+    path = "/manager_api/reload_config",
+    responses(
+        (status = 200, description = "Config reloaded.", body = ConfigReloadReport),
+        (status = 500, description = "Internal server error. Previous config is still in use."),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn post_reload_config<S: GetConfigReloader>(
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Result<Json<ConfigReloadReport>, StatusCode> {
+    info!("Config reload request received from {}", client);
+
+    let report = state.config_reloader().reload().await?;
+    Ok(report.into())
+}
+
 pub const PATH_GET_SOFTWARE_INFO: &str = "/manager_api/software_info";
 
 /// Get current software info about currently installed backend and manager.
@@ -258,19 +516,25 @@ pub const PATH_GET_SYSTEM_INFO: &str = "/manager_api/system_info";
 #[utoipa::path(
     get,
     path = "/manager_api/system_info",
+    params(SystemInfoSectionsQueryParam),
     responses(
         (status = 200, description = "System info", body = SystemInfo),
-        (status = 500, description = "Internal server error."),
+        (status = 400, description = "Invalid sections."),
     ),
     security(("api_key" = [])),
 )]
 pub async fn get_system_info<S: GetConfig>(
+    Query(sections): Query<SystemInfoSectionsQueryParam>,
     ConnectInfo(client): ConnectInfo<SocketAddr>,
     state: S,
 ) -> Result<Json<SystemInfo>, StatusCode> {
     info!("Get current system info received from {}.", client,);
 
-    let info = SystemInfoGetter::system_info(state.config()).await?;
+    let sections = sections.sections().map_err(|e| {
+        warn!("Invalid system info sections: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let info = SystemInfoGetter::system_info(state.config(), &sections).await;
     Ok(info.into())
 }
 
@@ -283,18 +547,67 @@ pub const PATH_GET_SYSTEM_INFO_ALL: &str = "/manager_api/system_info_all";
 #[utoipa::path(
     get,
     path = "/manager_api/system_info_all",
+    params(SystemInfoSectionsQueryParam),
     responses(
         (status = 200, description = "Get all system infos available", body = SystemInfoList),
-        (status = 500, description = "Internal server error."),
+        (status = 400, description = "Invalid sections."),
     ),
     security(("api_key" = [])),
 )]
 pub async fn get_system_info_all<S: GetConfig + GetApiManager>(
+    Query(sections): Query<SystemInfoSectionsQueryParam>,
     ConnectInfo(client): ConnectInfo<SocketAddr>,
     state: S,
 ) -> Result<Json<SystemInfoList>, StatusCode> {
     info!("Get all system infos received from {}.", client,);
 
-    let info = SystemInfoGetter::system_info_all(state.config(), &state.api_manager()).await?;
+    let sections = sections.sections().map_err(|e| {
+        warn!("Invalid system info sections: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let info =
+        SystemInfoGetter::system_info_all(state.config(), &state.api_manager(), &sections).await;
     Ok(info.into())
 }
+
+pub const PATH_GET_LOGS: &str = "/manager_api/logs";
+
+/// Get journal logs of a service.
+///
+/// The service must be listed in system info log_services config. Log
+/// lines are sent as a chunked plain text response. If follow is true,
+/// the response stays open and new log lines are sent when they appear.
+#[utoipa::path(
+    get,
+    path = "/manager_api/logs",
+    params(LogsQueryParam),
+    responses(
+        (status = 200, description = "Log lines", body = String, content_type = "text/plain"),
+        (status = 400, description = "Invalid priority."),
+        (status = 404, description = "Service is not configured."),
+        (status = 500, description = "Internal server error."),
+    ),
+    security(("api_key" = [])),
+)]
+pub async fn get_logs<S: GetConfig>(
+    Query(query): Query<LogsQueryParam>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    state: S,
+) -> Result<impl IntoResponse, StatusCode> {
+    info!(
+        "Get logs request received from {}. service: {}, follow: {}",
+        client, query.service, query.follow,
+    );
+
+    let lines =
+        JournalReader::read(state.config(), query).map_err(|e| match e.current_context() {
+            SystemInfoError::UnknownLogService => StatusCode::NOT_FOUND,
+            SystemInfoError::InvalidInput => StatusCode::BAD_REQUEST,
+            _ => e.into(),
+        })?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        Body::from_stream(lines),
+    ))
+}
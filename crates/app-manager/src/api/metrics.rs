@@ -0,0 +1,17 @@
+//! Prometheus metrics.
+//!
+//! This route is not part of the manager API, so it is not included in
+//! the API docs.
+
+use axum::response::IntoResponse;
+use hyper::header;
+
+use super::utils::StatusCode;
+use crate::server::metrics::METRICS;
+
+pub const PATH_GET_METRICS: &str = "/metrics";
+
+pub async fn get_metrics() -> Result<impl IntoResponse, StatusCode> {
+    let text = METRICS.encode()?;
+    Ok(([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], text))
+}
@@ -1,11 +1,8 @@
-use std::{
-    net::SocketAddr,
-    sync::atomic::{AtomicBool, Ordering},
-};
+use std::net::SocketAddr;
 
 use axum::{
     body::Body,
-    extract::ConnectInfo,
+    extract::{ConnectInfo, MatchedPath},
     middleware::Next,
     response::{IntoResponse, Response},
 };
@@ -16,34 +13,51 @@ use utoipa::{
     Modify,
 };
 
-use super::GetConfig;
+use super::{GetConfig, GetStateStorage};
 use crate::{
     config::GetConfigError,
-    server::{build::BuildError, client::ApiError, info::SystemInfoError, update::UpdateError},
+    server::{
+        backend_controller::ControllerError,
+        build::BuildError,
+        client::ApiError,
+        info::SystemInfoError,
+        metrics::{MetricsError, METRICS},
+        reboot::RebootError,
+        reboot_coordinator::CoordinatorError,
+        reload::ReloadError,
+        update::UpdateError,
+    },
 };
 
-/// If true then password has been guessed and manager API is now locked.
-static API_SECURITY_LOCK: AtomicBool = AtomicBool::new(false);
-
 pub const API_KEY_HEADER_STR: &str = "x-api-key";
 pub static API_KEY_HEADER: header::HeaderName = header::HeaderName::from_static(API_KEY_HEADER_STR);
 
-pub async fn authenticate_with_api_key<S: GetConfig>(
+/// Manager API is locked if the API key has been guessed. The lock is
+/// stored in the state file.
+pub async fn authenticate_with_api_key<S: GetConfig + GetStateStorage>(
     state: S,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     req: Request<Body>,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let header = req
-        .headers()
-        .get(API_KEY_HEADER_STR)
-        .ok_or(StatusCode::BAD_REQUEST)?;
-    let key_str = header.to_str().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let header = req.headers().get(API_KEY_HEADER_STR).ok_or_else(|| {
+        METRICS.api_auth_failure("missing_key");
+        StatusCode::BAD_REQUEST
+    })?;
+    let key_str = header.to_str().map_err(|_| {
+        METRICS.api_auth_failure("invalid_key");
+        StatusCode::BAD_REQUEST
+    })?;
 
-    if API_SECURITY_LOCK.load(Ordering::Relaxed) {
+    if state.state_storage().get(|s| s.persistent.api_locked).await {
+        METRICS.api_auth_failure("locked");
         Err(StatusCode::LOCKED)
     } else if state.config().api_key() != key_str {
-        API_SECURITY_LOCK.store(true, Ordering::Relaxed);
+        state
+            .state_storage()
+            .modify(|s| s.persistent.api_locked = true)
+            .await;
+        METRICS.api_auth_failure("wrong_key");
         tracing::error!(
             "API key has been guessed. API is now locked. Guesser information, addr: {}",
             addr
@@ -54,6 +68,18 @@ pub async fn authenticate_with_api_key<S: GetConfig>(
     }
 }
 
+/// Count requests by matched route and response status.
+pub async fn record_api_request_metrics(req: Request<Body>, next: Next) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let response = next.run(req).await;
+    METRICS.api_request(&route, response.status().as_u16());
+    response
+}
+
 pub struct ApiKeyHeader(String);
 
 impl ApiKeyHeader {
@@ -115,6 +141,8 @@ pub enum StatusCode {
     NOT_MODIFIED,
     /// 423
     LOCKED,
+    /// 409
+    CONFLICT,
 }
 
 impl From<StatusCode> for hyper::StatusCode {
@@ -127,6 +155,7 @@ impl From<StatusCode> for hyper::StatusCode {
             StatusCode::NOT_FOUND => hyper::StatusCode::NOT_FOUND,
             StatusCode::NOT_MODIFIED => hyper::StatusCode::NOT_MODIFIED,
             StatusCode::LOCKED => hyper::StatusCode::LOCKED,
+            StatusCode::CONFLICT => hyper::StatusCode::CONFLICT,
         }
     }
 }
@@ -154,6 +183,21 @@ enum RequestError {
 
     #[error("System info error")]
     SystemInfo,
+
+    #[error("Config reload error")]
+    Reload,
+
+    #[error("Metrics error")]
+    Metrics,
+
+    #[error("Backend controller error")]
+    BackendController,
+
+    #[error("Reboot manager error")]
+    Reboot,
+
+    #[error("Reboot coordinator error")]
+    RebootCoordinator,
 }
 
 impl From<error_stack::Report<UpdateError>> for StatusCode {
@@ -195,3 +239,49 @@ impl From<error_stack::Report<SystemInfoError>> for StatusCode {
         StatusCode::INTERNAL_SERVER_ERROR
     }
 }
+
+impl From<error_stack::Report<ReloadError>> for StatusCode {
+    #[track_caller]
+    fn from(value: error_stack::Report<ReloadError>) -> Self {
+        tracing::error!("{:?}", value.change_context(RequestError::Reload));
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl From<error_stack::Report<MetricsError>> for StatusCode {
+    #[track_caller]
+    fn from(value: error_stack::Report<MetricsError>) -> Self {
+        tracing::error!("{:?}", value.change_context(RequestError::Metrics));
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl From<error_stack::Report<ControllerError>> for StatusCode {
+    #[track_caller]
+    fn from(value: error_stack::Report<ControllerError>) -> Self {
+        tracing::error!(
+            "{:?}",
+            value.change_context(RequestError::BackendController)
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl From<error_stack::Report<RebootError>> for StatusCode {
+    #[track_caller]
+    fn from(value: error_stack::Report<RebootError>) -> Self {
+        tracing::error!("{:?}", value.change_context(RequestError::Reboot));
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+impl From<error_stack::Report<CoordinatorError>> for StatusCode {
+    #[track_caller]
+    fn from(value: error_stack::Report<CoordinatorError>) -> Self {
+        tracing::error!(
+            "{:?}",
+            value.change_context(RequestError::RebootCoordinator)
+        );
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
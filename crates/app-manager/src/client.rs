@@ -1,9 +1,11 @@
 //! CLI API client
 //!
 
+use std::{io::Write, path::Path};
+
 use error_stack::{Result, ResultExt};
 use manager_api::{ApiKey, Configuration, ManagerApi};
-use manager_model::ResetDataQueryParam;
+use manager_model::{LogsQueryParam, ResetDataQueryParam, SystemInfoSectionsQueryParam};
 use reqwest::Certificate;
 use url::Url;
 
@@ -12,17 +14,29 @@ use crate::{
     server::client::ApiError,
 };
 
-pub async fn handle_api_client_mode(args: ApiClientMode) -> Result<(), ApiError> {
+pub async fn handle_api_client_mode(
+    args: ApiClientMode,
+    config_file: &Path,
+) -> Result<(), ApiError> {
     let api_key = args
-        .api_key()
+        .api_key(config_file)
         .change_context(ApiError::MissingConfiguration)?;
     let api_url = args
-        .api_url()
+        .api_url(config_file)
+        .change_context(ApiError::MissingConfiguration)?;
+    let certificates = args
+        .root_certificates(config_file)
         .change_context(ApiError::MissingConfiguration)?;
-    let certificate = args
-        .root_certificate()
+    let use_system_root_certificates = args
+        .use_system_root_certificates(config_file)
         .change_context(ApiError::MissingConfiguration)?;
-    let configuration = create_configration(api_key, api_url, certificate)?;
+    if api_url.scheme() == "https" && certificates.is_empty() && !use_system_root_certificates {
+        return Err(ApiError::MissingConfiguration).attach_printable(
+            "Root certificate is not configured. Use --root-certificate or --system-root-certificates",
+        );
+    }
+    let configuration =
+        create_configration(api_key, api_url, certificates, use_system_root_certificates)?;
 
     match args.api_command {
         ApiCommand::EncryptionKey {
@@ -70,18 +84,93 @@ pub async fn handle_api_client_mode(args: ApiClientMode) -> Result<(), ApiError>
                 .change_context(ApiError::ApiRequest)?;
             println!("Restart backend requested, reset_data: {}", reset_data);
         }
-        ApiCommand::SystemInfoAll => {
-            let info = ManagerApi::system_info_all(&configuration)
+        ApiCommand::ReloadConfig => {
+            let report = ManagerApi::reload_config(&configuration)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            println!("{:#?}", report);
+        }
+        ApiCommand::SystemInfoAll { sections } => {
+            let sections = SystemInfoSectionsQueryParam::new(&sections);
+            let info = ManagerApi::system_info_all(&configuration, &sections)
                 .await
                 .change_context(ApiError::ApiRequest)?;
             println!("{:#?}", info);
         }
-        ApiCommand::SystemInfo => {
-            let info = ManagerApi::system_info(&configuration)
+        ApiCommand::SystemInfo { sections } => {
+            let sections = SystemInfoSectionsQueryParam::new(&sections);
+            let info = ManagerApi::system_info(&configuration, &sections)
                 .await
                 .change_context(ApiError::ApiRequest)?;
             println!("{:#?}", info);
         }
+        ApiCommand::Logs {
+            service,
+            follow,
+            priority,
+            since,
+        } => {
+            let query = LogsQueryParam {
+                service,
+                follow,
+                priority,
+                since,
+            };
+            let mut logs = ManagerApi::logs(&configuration, &query)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            let mut stdout = std::io::stdout();
+            while let Some(chunk) = logs
+                .next_chunk()
+                .await
+                .change_context(ApiError::ApiRequest)?
+            {
+                stdout
+                    .write_all(&chunk)
+                    .and_then(|()| stdout.flush())
+                    .change_context(ApiError::ApiRequest)?;
+            }
+        }
+        ApiCommand::BackendStatus => {
+            let status = ManagerApi::backend_status(&configuration)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            println!("{:#?}", status);
+        }
+        ApiCommand::UpdateStatus => {
+            let status = ManagerApi::update_status(&configuration)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            println!("{:#?}", status);
+        }
+        ApiCommand::RebootStatus => {
+            let status = ManagerApi::reboot_status(&configuration)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            println!("{:#?}", status);
+        }
+        ApiCommand::ScheduleReboot => {
+            ManagerApi::schedule_reboot(&configuration)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            println!("Reboot scheduled");
+        }
+        ApiCommand::RebootNow => {
+            ManagerApi::reboot_now(&configuration)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            println!("Reboot requested");
+        }
+        ApiCommand::CancelReboot => {
+            let result = ManagerApi::cancel_reboot(&configuration)
+                .await
+                .change_context(ApiError::ApiRequest)?;
+            if result.was_pending {
+                println!("Pending reboot cancelled");
+            } else {
+                println!("No pending reboot");
+            }
+        }
         ApiCommand::SoftwareInfo => {
             let info = ManagerApi::software_info(&configuration)
                 .await
@@ -96,21 +185,22 @@ pub async fn handle_api_client_mode(args: ApiClientMode) -> Result<(), ApiError>
 pub fn create_configration(
     api_key: String,
     base_url: Url,
-    root_certificate: Option<Certificate>,
+    root_certificates: Vec<Certificate>,
+    use_system_root_certificates: bool,
 ) -> Result<Configuration, ApiError> {
     let api_key = ApiKey {
         prefix: None,
         key: api_key,
     };
 
-    let client = reqwest::ClientBuilder::new().tls_built_in_root_certs(false);
-    let client = if let Some(cert) = root_certificate {
-        client.add_root_certificate(cert)
-    } else {
-        client
-    }
-    .build()
-    .change_context(ApiError::ClientBuildFailed)?;
+    let client = root_certificates
+        .into_iter()
+        .fold(
+            reqwest::ClientBuilder::new().tls_built_in_root_certs(use_system_root_certificates),
+            |client, cert| client.add_root_certificate(cert),
+        )
+        .build()
+        .change_context(ApiError::ClientBuildFailed)?;
 
     let url = base_url.as_str().trim_end_matches('/').to_string();
 
@@ -1,26 +1,36 @@
 use std::{
     io::BufReader,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, RwLock},
+    time::Duration,
     vec,
 };
 
 use error_stack::{Result, ResultExt};
+use manager_model::ConfigReloadReport;
 use rustls_pemfile::certs;
-use tokio_rustls::rustls::ServerConfig;
+use serde::Serialize;
+use tokio_rustls::rustls::{
+    pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer},
+    ServerConfig,
+};
 use tracing::{info, log::warn};
 
 use self::{
     args::ArgsConfig,
     file::{
-        ConfigFile, RebootIfNeededConfig, SecureStorageConfig, ServerEncryptionKey, SocketConfig,
-        SoftwareBuilderConfig, SoftwareUpdateProviderConfig, SystemInfoConfig,
+        BackendControllerType, BackendStopConfig, BackendSupervisorConfig, ConfigFile,
+        HooksConfig, LoggingConfig, RebootCoordinatorConfig, RebootIfNeededConfig,
+        SecureStorageConfig, ServerEncryptionKey, SocketConfig, SoftwareBuilderConfig,
+        SoftwareUpdateProviderConfig, SystemInfoConfig, TlsConfig,
     },
 };
 
 pub mod args;
+pub mod check;
 pub mod file;
 pub mod info;
+pub mod init;
 
 #[derive(thiserror::Error, Debug)]
 pub enum GetConfigError {
@@ -49,16 +59,61 @@ pub enum GetConfigError {
     InvalidConstant,
     #[error("Certificate file reading failed")]
     ReadCertificateError,
+    #[error("Config reload failed")]
+    ReloadFailed,
+    #[error("Config file creation failed")]
+    InitFailed,
+}
+
+/// Top level config file fields which can be changed without restart.
+const RELOADABLE_FIELDS: &[&str] = &["server_encryption_keys", "reboot_if_needed", "system_info"];
+
+pub const DEFAULT_BACKEND_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Config values which can be changed without restarting the manager.
+///
+/// Field names must match [RELOADABLE_FIELDS].
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadableConfig {
+    server_encryption_keys: Option<Vec<ServerEncryptionKey>>,
+    reboot_if_needed: Option<RebootIfNeededConfig>,
+    system_info: Option<SystemInfoConfig>,
+}
+
+impl ReloadableConfig {
+    fn new(file: &ConfigFile) -> Self {
+        Self {
+            server_encryption_keys: file.server_encryption_keys.clone(),
+            reboot_if_needed: file.reboot_if_needed.clone(),
+            system_info: file.system_info.clone(),
+        }
+    }
+
+    pub fn encryption_keys(&self) -> &[ServerEncryptionKey] {
+        self.server_encryption_keys.as_deref().unwrap_or(&[])
+    }
+
+    pub fn reboot_if_needed(&self) -> Option<&RebootIfNeededConfig> {
+        self.reboot_if_needed.as_ref()
+    }
+
+    pub fn system_info(&self) -> Option<&SystemInfoConfig> {
+        self.system_info.as_ref()
+    }
 }
 
 #[derive(Debug)]
 pub struct Config {
+    /// Config file which was loaded when the manager started.
     file: ConfigFile,
+    /// Config file location.
+    config_file: PathBuf,
+    reloadable: RwLock<Arc<ReloadableConfig>>,
     script_locations: ScriptLocations,
 
     // TLS
     public_api_tls_config: Option<Arc<ServerConfig>>,
-    root_certificate: Option<reqwest::Certificate>,
+    root_certificates: Vec<reqwest::Certificate>,
 }
 
 impl Config {
@@ -76,13 +131,6 @@ impl Config {
         self.file.debug.unwrap_or(false)
     }
 
-    pub fn encryption_keys(&self) -> &[ServerEncryptionKey] {
-        self.file
-            .server_encryption_keys
-            .as_deref()
-            .unwrap_or(&[])
-    }
-
     pub fn secure_storage_config(&self) -> Option<&SecureStorageConfig> {
         self.file.secure_storage.as_ref()
     }
@@ -91,28 +139,122 @@ impl Config {
         self.file.software_update_provider.as_ref()
     }
 
+    /// If backend_controller is not configured, it is selected using
+    /// backend_supervisor and backend_systemd_unit.
+    pub fn backend_controller_type(&self) -> BackendControllerType {
+        let Some(update) = self.software_update_provider() else {
+            return BackendControllerType::Script;
+        };
+        if let Some(controller) = update.backend_controller {
+            controller
+        } else if update.backend_supervisor.is_some() {
+            BackendControllerType::Process
+        } else if update.backend_systemd_unit.is_some() {
+            BackendControllerType::Systemd
+        } else {
+            BackendControllerType::Script
+        }
+    }
+
+    /// Backend is supervised by the manager if this is Some.
+    pub fn backend_supervisor(&self) -> Option<&BackendSupervisorConfig> {
+        if self.backend_controller_type() != BackendControllerType::Process {
+            return None;
+        }
+        self.software_update_provider()
+            .and_then(|update| update.backend_supervisor.as_ref())
+    }
+
+    /// Manager update restarts the manager instead of rebooting.
+    pub fn manager_self_update(&self) -> bool {
+        self.software_update_provider()
+            .and_then(|update| update.manager_self_update)
+            .unwrap_or(false)
+    }
+
+    pub fn backend_stop(&self) -> Option<&BackendStopConfig> {
+        self.software_update_provider()
+            .and_then(|update| update.backend_stop.as_ref())
+    }
+
+    /// Time to wait after SIGTERM before the backend is killed.
+    ///
+    /// Script controller has no stop timeout unless it is configured.
+    pub fn backend_stop_timeout(&self) -> Option<Duration> {
+        let configured = self
+            .backend_stop()
+            .and_then(|stop| stop.stop_timeout_seconds)
+            .or_else(|| {
+                self.software_update_provider()
+                    .and_then(|update| update.backend_supervisor.as_ref())
+                    .and_then(|supervisor| supervisor.stop_timeout_seconds)
+            })
+            .map(|seconds| Duration::from_secs(seconds.into()));
+        if self.backend_controller_type() == BackendControllerType::Script {
+            configured
+        } else {
+            Some(configured.unwrap_or(DEFAULT_BACKEND_STOP_TIMEOUT))
+        }
+    }
+
+    /// Backend is controlled using systemd if this is Some.
+    pub fn backend_systemd_unit(&self) -> Option<&str> {
+        if self.backend_controller_type() != BackendControllerType::Systemd {
+            return None;
+        }
+        self.software_update_provider()
+            .and_then(|update| update.backend_systemd_unit.as_deref())
+    }
+
+    pub fn reboot_coordinator(&self) -> Option<&RebootCoordinatorConfig> {
+        self.file.reboot_coordinator.as_ref()
+    }
+
+    pub fn hooks(&self) -> Option<&HooksConfig> {
+        self.file.hooks.as_ref()
+    }
+
     pub fn software_builder(&self) -> Option<&SoftwareBuilderConfig> {
         self.file.software_builder.as_ref()
     }
 
     pub fn api_key(&self) -> &str {
-        &self.file.api_key
+        self.file.api_key.as_str()
+    }
+
+    /// Warnings about secret files which have too broad permissions.
+    pub fn secret_permission_warnings(&self) -> Vec<String> {
+        self.file
+            .secrets()
+            .into_iter()
+            .filter_map(|(_, secret)| secret.permission_warning())
+            .collect()
     }
 
     pub fn public_api_tls_config(&self) -> Option<&Arc<ServerConfig>> {
         self.public_api_tls_config.as_ref()
     }
 
-    pub fn root_certificate(&self) -> Option<&reqwest::Certificate> {
-        self.root_certificate.as_ref()
+    /// TLS file locations from config file.
+    pub fn tls_config(&self) -> Option<&TlsConfig> {
+        self.file.tls.as_ref()
     }
 
-    pub fn script_locations(&self) -> &ScriptLocations {
-        &self.script_locations
+    pub fn root_certificates(&self) -> &[reqwest::Certificate] {
+        &self.root_certificates
     }
 
-    pub fn system_info(&self) -> Option<&SystemInfoConfig> {
-        self.file.system_info.as_ref()
+    /// HTTP client should trust operating system root certificates.
+    pub fn use_system_root_certificates(&self) -> bool {
+        self.file
+            .tls
+            .as_ref()
+            .and_then(|tls| tls.use_system_root_certificates)
+            .unwrap_or(false)
+    }
+
+    pub fn script_locations(&self) -> &ScriptLocations {
+        &self.script_locations
     }
 
     /// Directory for build and update files
@@ -120,31 +262,102 @@ impl Config {
         &self.file.storage_dir
     }
 
-    pub fn reboot_if_needed(&self) -> &Option<RebootIfNeededConfig> {
-        &self.file.reboot_if_needed
-    }
-
     pub fn log_timestamp(&self) -> bool {
         self.file.log_timestamp.unwrap_or(true)
     }
+
+    pub fn logging(&self) -> Option<&LoggingConfig> {
+        self.file.logging.as_ref()
+    }
+
+    /// Config values which can be changed without restart.
+    pub fn reloadable(&self) -> Arc<ReloadableConfig> {
+        match self.reloadable.read() {
+            Ok(reloadable) => reloadable.clone(),
+            Err(e) => e.into_inner().clone(),
+        }
+    }
+
+    /// Load and validate config file again and replace values which
+    /// can be changed without restart. Current config is kept if the
+    /// new config has problems.
+    pub fn reload(&self) -> Result<ConfigReloadReport, GetConfigError> {
+        let new_config = load_config(&self.config_file)?;
+        let (problems, _) = check::check_config(&new_config.file);
+        if !problems.is_empty() {
+            return Err(GetConfigError::ReloadFailed).attach_printable(problems.join("\n"));
+        }
+        let new_reloadable = new_config.reloadable();
+
+        let report = ConfigReloadReport {
+            reloaded: changed_fields(&*self.reloadable(), &*new_reloadable, true)?,
+            restart_required: changed_fields(&self.file, &new_config.file, false)?,
+        };
+
+        match self.reloadable.write() {
+            Ok(mut reloadable) => *reloadable = new_reloadable,
+            Err(e) => *e.into_inner() = new_reloadable,
+        }
+
+        Ok(report)
+    }
+}
+
+/// Compare top level config fields. Returns names of reloadable or
+/// not reloadable fields which have different values.
+fn changed_fields(
+    old: &impl Serialize,
+    new: &impl Serialize,
+    reloadable: bool,
+) -> Result<Vec<String>, GetConfigError> {
+    let old = toml::Value::try_from(old).change_context(GetConfigError::ReloadFailed)?;
+    let new = toml::Value::try_from(new).change_context(GetConfigError::ReloadFailed)?;
+    let (Some(old), Some(new)) = (old.as_table(), new.as_table()) else {
+        return Err(GetConfigError::ReloadFailed).attach_printable("Config is not a table");
+    };
+
+    let mut changed: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|key| old.get(*key) != new.get(*key))
+        .filter(|key| RELOADABLE_FIELDS.contains(&key.as_str()) == reloadable)
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    Ok(changed)
 }
 
-pub fn get_config(_args: ArgsConfig) -> Result<Config, GetConfigError> {
-    let current_dir = std::env::current_dir().change_context(GetConfigError::GetWorkingDir)?;
-    let file_config = file::ConfigFile::save_default_if_not_exist_and_load(current_dir)
+pub fn get_config(args: ArgsConfig) -> Result<Config, GetConfigError> {
+    let config_file = args.config_file()?;
+    if !config_file.exists() {
+        return Err(GetConfigError::LoadFileError).attach_printable(format!(
+            "Config file {} does not exist. Create it with 'app-manager init'.",
+            config_file.display()
+        ));
+    }
+    load_config(&config_file)
+}
+
+/// Load config file and validate it.
+pub fn load_config(config_file: &Path) -> Result<Config, GetConfigError> {
+    let file_config = file::ConfigFile::load_config_file(config_file)
         .change_context(GetConfigError::LoadFileError)?;
+    create_config(file_config, config_file.to_path_buf())
+}
 
-    let public_api_tls_config = match file_config.tls.clone() {
-        Some(tls_config) => Some(Arc::new(generate_server_config(
-            tls_config.public_api_key.as_path(),
-            tls_config.public_api_cert.as_path(),
-        )?)),
+fn create_config(file_config: ConfigFile, config_file: PathBuf) -> Result<Config, GetConfigError> {
+    let public_api_tls_config = match file_config.tls.as_ref() {
+        Some(tls_config) => Some(load_public_api_tls_config(
+            tls_config,
+            &file_config.storage_dir,
+        )?),
         None => None,
     };
 
-    let root_certificate = match file_config.tls.clone() {
-        Some(tls_config) => Some(load_root_certificate(&tls_config.root_certificate)?),
-        None => None,
+    let root_certificates = match file_config.tls.as_ref() {
+        Some(tls_config) => load_root_certificates(&tls_config.root_certificate)?,
+        None => vec![],
     };
 
     if public_api_tls_config.is_none() && !file_config.debug.unwrap_or_default() {
@@ -158,10 +371,12 @@ pub fn get_config(_args: ArgsConfig) -> Result<Config, GetConfigError> {
     )?;
 
     Ok(Config {
+        reloadable: RwLock::new(ReloadableConfig::new(&file_config).into()),
         file: file_config,
+        config_file,
         script_locations,
         public_api_tls_config,
-        root_certificate,
+        root_certificates,
     })
 }
 
@@ -169,43 +384,8 @@ fn check_script_locations(
     script_dir: &Path,
     is_debug: bool,
 ) -> Result<ScriptLocations, GetConfigError> {
-    let open_encryption = script_dir.join("open-encryption.sh");
-    let close_encryption = script_dir.join("close-encryption.sh");
-    let is_default_encryption_password = script_dir.join("is-default-encryption-password.sh");
-    let change_encryption_password = script_dir.join("change-encryption-password.sh");
-    let start_backend = script_dir.join("start-backend.sh");
-    let stop_backend = script_dir.join("stop-backend.sh");
-    let print_logs = script_dir.join("print-logs.sh");
-
-    let mut errors = vec![];
-
-    if !open_encryption.exists() {
-        errors.push(format!("Script not found: {}", open_encryption.display()));
-    }
-    if !close_encryption.exists() {
-        errors.push(format!("Script not found: {}", close_encryption.display()));
-    }
-    if !is_default_encryption_password.exists() {
-        errors.push(format!(
-            "Script not found: {}",
-            is_default_encryption_password.display()
-        ));
-    }
-    if !change_encryption_password.exists() {
-        errors.push(format!(
-            "Script not found: {}",
-            change_encryption_password.display()
-        ));
-    }
-    if !start_backend.exists() {
-        errors.push(format!("Script not found: {}", start_backend.display()));
-    }
-    if !stop_backend.exists() {
-        errors.push(format!("Script not found: {}", stop_backend.display()));
-    }
-    if !print_logs.exists() {
-        errors.push(format!("Script not found: {}", print_logs.display()));
-    }
+    let locations = ScriptLocations::new(script_dir);
+    let errors = locations.missing_scripts();
 
     if errors.is_empty() || is_debug {
         if errors.is_empty() {
@@ -213,38 +393,98 @@ fn check_script_locations(
         } else {
             warn!("Some scripts are missing.\n{}", errors.join("\n"));
         }
-        Ok(ScriptLocations {
-            open_encryption,
-            close_encryption,
-            is_default_encryption_password,
-            change_encryption_password,
-            start_backend,
-            stop_backend,
-            print_logs,
-        })
+        Ok(locations)
     } else {
         Err(GetConfigError::ScriptLocationError).attach_printable(errors.join("\n"))
     }
 }
 
-fn load_root_certificate(cert_path: &Path) -> Result<reqwest::Certificate, GetConfigError> {
+impl ScriptLocations {
+    fn new(script_dir: &Path) -> Self {
+        Self {
+            open_encryption: script_dir.join("open-encryption.sh"),
+            close_encryption: script_dir.join("close-encryption.sh"),
+            is_default_encryption_password: script_dir.join("is-default-encryption-password.sh"),
+            change_encryption_password: script_dir.join("change-encryption-password.sh"),
+            start_backend: script_dir.join("start-backend.sh"),
+            stop_backend: script_dir.join("stop-backend.sh"),
+            print_logs: script_dir.join("print-logs.sh"),
+        }
+    }
+
+    /// Returns error message for every missing script.
+    fn missing_scripts(&self) -> Vec<String> {
+        [
+            &self.open_encryption,
+            &self.close_encryption,
+            &self.is_default_encryption_password,
+            &self.change_encryption_password,
+            &self.start_backend,
+            &self.stop_backend,
+            &self.print_logs,
+        ]
+        .into_iter()
+        .filter(|script| !script.exists())
+        .map(|script| format!("Script not found: {}", script.display()))
+        .collect()
+    }
+}
+
+/// Load all certificates from a PEM file.
+pub fn load_root_certificates(
+    cert_path: &Path,
+) -> Result<Vec<reqwest::Certificate>, GetConfigError> {
     let mut cert_reader = BufReader::new(
         std::fs::File::open(cert_path).change_context(GetConfigError::CreateTlsConfig)?,
     );
-    let all_certs: Vec<_> = certs(&mut cert_reader).collect();
-    let mut cert_iter = all_certs.into_iter();
-    let cert = if let Some(cert) = cert_iter.next() {
+
+    let mut root_certs = vec![];
+    for cert in certs(&mut cert_reader) {
         let cert = cert.change_context(GetConfigError::CreateTlsConfig)?;
-        reqwest::Certificate::from_der(&cert).change_context(GetConfigError::CreateTlsConfig)?
-    } else {
+        let cert = reqwest::Certificate::from_der(&cert)
+            .change_context(GetConfigError::CreateTlsConfig)?;
+        root_certs.push(cert);
+    }
+
+    if root_certs.is_empty() {
         return Err(GetConfigError::CreateTlsConfig).attach_printable("No cert found");
-    };
+    }
 
-    if cert_iter.next().is_some() {
-        return Err(GetConfigError::CreateTlsConfig).attach_printable("Only one cert supported");
+    Ok(root_certs)
+}
+
+/// Load public API TLS certificate and key from files.
+///
+/// This is also used for reloading the certificate while the server is
+/// running. If ACME is enabled and the certificate is not yet available,
+/// a self-signed certificate is used.
+pub fn load_public_api_tls_config(
+    tls_config: &TlsConfig,
+    storage_dir: &Path,
+) -> Result<Arc<ServerConfig>, GetConfigError> {
+    let (cert_path, key_path) = tls_config
+        .public_api_cert_and_key(storage_dir)
+        .ok_or(GetConfigError::TlsConfigMissing)
+        .attach_printable("public_api_cert and public_api_key are required if ACME is disabled")?;
+
+    match &tls_config.acme {
+        Some(acme) if !cert_path.exists() || !key_path.exists() => {
+            warn!("ACME certificate is not available. Using self-signed certificate.");
+            generate_self_signed_server_config(&acme.domains).map(Arc::new)
+        }
+        _ => generate_server_config(&key_path, &cert_path).map(Arc::new),
     }
+}
+
+fn generate_self_signed_server_config(domains: &[String]) -> Result<ServerConfig, GetConfigError> {
+    let cert = rcgen::generate_simple_self_signed(domains.to_vec())
+        .change_context(GetConfigError::CreateTlsConfig)?;
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()));
 
-    Ok(cert)
+    ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert.cert.der().clone()], key)
+        .change_context(GetConfigError::CreateTlsConfig)
 }
 
 fn generate_server_config(
@@ -275,23 +515,19 @@ fn generate_server_config(
         std::fs::File::open(cert_path).change_context(GetConfigError::CreateTlsConfig)?,
     );
 
-    let all_certs: Vec<_> = certs(&mut cert_reader)
+    // The file can contain a certificate chain. ACME servers return
+    // the server certificate and intermediate certificates.
+    let cert_chain: Vec<_> = certs(&mut cert_reader)
         .map(|r| r.map(|c| c.into_owned()))
-        .collect();
-    let mut cert_iter = all_certs.into_iter();
-    let cert = if let Some(cert) = cert_iter.next() {
-        cert.change_context(GetConfigError::CreateTlsConfig)?
-    } else {
+        .collect::<std::result::Result<_, _>>()
+        .change_context(GetConfigError::CreateTlsConfig)?;
+    if cert_chain.is_empty() {
         return Err(GetConfigError::CreateTlsConfig).attach_printable("No cert found");
-    };
-
-    if cert_iter.next().is_some() {
-        return Err(GetConfigError::CreateTlsConfig).attach_printable("Only one cert supported");
     }
 
     let config = ServerConfig::builder()
         .with_no_client_auth() // TODO: configure at some point
-        .with_single_cert(vec![cert], key)
+        .with_single_cert(cert_chain, key)
         .change_context(GetConfigError::CreateTlsConfig)?;
 
     Ok(config)
@@ -337,3 +573,72 @@ impl ScriptLocations {
         &self.print_logs
     }
 }
+
+/// Load config with debug mode enabled. Storage directory is created
+/// to the directory and the extra config is appended to the config file.
+#[cfg(test)]
+pub fn load_test_config(dir: &Path, extra_config: &str) -> Config {
+    let storage_dir = dir.join("storage");
+    std::fs::create_dir_all(&storage_dir).unwrap();
+    let config_file = dir.join(file::CONFIG_FILE_NAME);
+    let config_text = format!(
+        r#"
+debug = true
+api_key = "password"
+storage_dir = "{}"
+scripts_dir = "{}"
+
+[socket]
+public_api = "127.0.0.1:5000"
+
+{}
+"#,
+        storage_dir.display(),
+        dir.display(),
+        extra_config,
+    );
+    std::fs::write(&config_file, config_text).unwrap();
+    load_config(&config_file).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update_provider(extra_config: &str) -> String {
+        format!(
+            r#"
+[software_update_provider]
+manager_base_url = "https://example.com:5000"
+manager_install_location = "app-manager"
+backend_install_location = "app-backend"
+{}
+"#,
+            extra_config
+        )
+    }
+
+    fn stop_timeout(extra_config: &str) -> Option<Duration> {
+        let dir = tempfile::tempdir().unwrap();
+        load_test_config(dir.path(), &update_provider(extra_config)).backend_stop_timeout()
+    }
+
+    #[test]
+    fn script_controller_has_no_default_stop_timeout() {
+        assert_eq!(stop_timeout(""), None);
+        assert_eq!(
+            stop_timeout("[software_update_provider.backend_stop]\nstop_timeout_seconds = 60"),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn process_controller_has_default_stop_timeout() {
+        let supervisor = "[software_update_provider.backend_supervisor]\n";
+        assert_eq!(stop_timeout(supervisor), Some(DEFAULT_BACKEND_STOP_TIMEOUT));
+        assert_eq!(
+            stop_timeout(&format!("{}stop_timeout_seconds = 5", supervisor)),
+            Some(Duration::from_secs(5))
+        );
+    }
+}
@@ -1,14 +1,20 @@
 //! Config given as command line arguments
 
-use std::{path::PathBuf, process::exit};
+use std::{
+    fmt::Display,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::exit,
+    str::FromStr,
+};
 
-use clap::{arg, command, Args, Parser};
+use clap::{arg, command, Args, Parser, ValueEnum};
 use error_stack::{Result, ResultExt};
-use manager_model::SoftwareOptions;
+use manager_model::{SoftwareOptions, SystemInfoSection};
 use reqwest::Certificate;
 use url::Url;
 
-use super::{file::ConfigFile, load_root_certificate, GetConfigError};
+use super::{file::ConfigFile, load_root_certificates, GetConfigError};
 
 const DEFAULT_HTTP_LOCALHOST_URL: &str = "http://localhost:5000";
 const DEFAULT_HTTPS_LOCALHOST_URL: &str = "https://localhost:5000";
@@ -20,10 +26,29 @@ pub struct ArgsConfig {
     #[arg(short, long)]
     pub build_info: bool,
 
+    /// Config file location. If not present, "manager_config.toml" from
+    /// current directory is used. Some config values can be overridden
+    /// using APP_MANAGER_* environment variables.
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
     #[command(subcommand)]
     pub app_mode: Option<AppMode>,
 }
 
+impl ArgsConfig {
+    pub fn config_file(&self) -> Result<PathBuf, GetConfigError> {
+        if let Some(config) = self.config.clone() {
+            Ok(config)
+        } else {
+            let current_dir =
+                std::env::current_dir().change_context(GetConfigError::GetWorkingDir)?;
+            ConfigFile::default_config_file_path(current_dir)
+                .change_context(GetConfigError::LoadFileError)
+        }
+    }
+}
+
 pub fn get_config() -> ArgsConfig {
     let matches = ArgsConfig::parse();
 
@@ -39,58 +64,141 @@ pub fn get_config() -> ArgsConfig {
 pub enum AppMode {
     /// Make API requests using CLI
     Api(ApiClientMode),
+    /// Validate config file and print all found problems
+    CheckConfig,
+    /// Create new config file
+    Init(InitMode),
+    /// Unlock manager API which was locked because of wrong API key.
+    /// Stop the manager before running this. The state file in the
+    /// storage directory must be available, so secure storage must be
+    /// mounted if the storage directory is located there.
+    UnlockApi,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct InitMode {
+    /// Ask values which are not given as arguments.
+    #[arg(short, long)]
+    pub interactive: bool,
+    /// Overwrite existing config file.
+    #[arg(long)]
+    pub force: bool,
+    /// Print example config with all available options and quit.
+    #[arg(long)]
+    pub print_example: bool,
+    /// Default value is app-server.
+    #[arg(long, value_enum)]
+    pub role: Option<ManagerRole>,
+    /// If not present, random API key is generated.
+    #[arg(long, value_name = "KEY")]
+    pub api_key: Option<String>,
+    #[arg(long, value_name = "DIR")]
+    pub scripts_dir: Option<PathBuf>,
+    #[arg(long, value_name = "DIR")]
+    pub storage_dir: Option<PathBuf>,
+    #[arg(long, value_name = "ADDR")]
+    pub public_api: Option<SocketAddr>,
+    /// Key provider and build server manager URL for app-server role.
+    #[arg(long, value_name = "URL")]
+    pub remote_manager_url: Option<Url>,
+    /// Encryption key name for key-provider and app-server roles.
+    #[arg(long, value_name = "NAME")]
+    pub encryption_key_name: Option<String>,
+    /// Encryption key file for key-provider role.
+    #[arg(long, value_name = "FILE")]
+    pub encryption_key_path: Option<PathBuf>,
+    /// Required for build-server role.
+    #[arg(long, value_name = "ADDRESS")]
+    pub manager_git_address: Option<String>,
+    /// Required for build-server role.
+    #[arg(long, value_name = "ADDRESS")]
+    pub backend_git_address: Option<String>,
+    #[arg(long, value_name = "FILE")]
+    pub tls_cert: Option<PathBuf>,
+    #[arg(long, value_name = "FILE")]
+    pub tls_key: Option<PathBuf>,
+    #[arg(long, value_name = "FILE")]
+    pub root_certificate: Option<PathBuf>,
+    /// Disable TLS. This enables debug mode.
+    #[arg(long)]
+    pub no_tls: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ManagerRole {
+    /// Provides encryption keys for other managers
+    KeyProvider,
+    /// Builds software from git repositories
+    BuildServer,
+    /// Runs backend and updates it from build server
+    AppServer,
+}
+
+impl Display for ManagerRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => write!(f, "{}", value.get_name()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for ManagerRole {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        <Self as ValueEnum>::from_str(s, true)
+    }
 }
 
 #[derive(Args, Debug, Clone)]
 pub struct ApiClientMode {
     /// API key for accessing the manager API. If not present, config file
-    /// api_key is tried to accessed from current directory.
+    /// api_key is used.
     #[arg(short = 'k', long, value_name = "KEY")]
     api_key: Option<String>,
     /// API URL for accessing the manager API. If not present, config file
-    /// TLS config is red from current directory. If it exists, then
+    /// TLS config is read. If it exists, then
     /// "https://localhost:5000" is used as the default value. If not, then
     /// "http://localhost:5000" is used as the default value.
     /// If config file does not exist, then "https://localhost:5000" is the
     /// default value.
     #[arg(short = 'u', long, value_name = "URL")]
     pub api_url: Option<Url>,
-    /// Root certificate for HTTP client. If not present, config file
-    /// TLS config is red from current directory. If it exists, then
-    /// root certificate value from there is used. If not, then HTTP client
-    /// uses system root certificates.
+    /// Root certificate for HTTP client. The file can contain multiple
+    /// certificates. If not present, config file
+    /// TLS config is read. If it exists, then
+    /// root certificate value from there is used.
     #[arg(short = 'c', long, value_name = "FILE")]
     pub root_certificate: Option<PathBuf>,
+    /// Trust system root certificates. If not present, config file
+    /// TLS config value use_system_root_certificates is used if it exists.
+    /// HTTPS requires a root certificate or this.
+    #[arg(short = 's', long)]
+    pub system_root_certificates: bool,
 
     #[command(subcommand)]
     pub api_command: ApiCommand,
 }
 
 impl ApiClientMode {
-    pub fn api_key(&self) -> Result<String, GetConfigError> {
+    pub fn api_key(&self, config_file: &Path) -> Result<String, GetConfigError> {
         if let Some(api_key) = self.api_key.clone() {
             Ok(api_key)
         } else {
-            let current_dir =
-                std::env::current_dir().change_context(GetConfigError::GetWorkingDir)?;
-            let file_config = ConfigFile::load_config(current_dir)
+            let file_config = ConfigFile::load_config_file(config_file)
                 .change_context(GetConfigError::LoadFileError)?;
 
-            Ok(file_config.api_key)
+            Ok(file_config.api_key.as_str().to_string())
         }
     }
 
-    pub fn api_url(&self) -> Result<Url, GetConfigError> {
+    pub fn api_url(&self, config_file: &Path) -> Result<Url, GetConfigError> {
         if let Some(api_url) = self.api_url.clone() {
             return Ok(api_url);
         }
 
-        let current_dir = std::env::current_dir().change_context(GetConfigError::GetWorkingDir)?;
-
-        let url_str = if ConfigFile::exists(&current_dir)
-            .change_context(GetConfigError::CheckConfigFileExistanceError)?
-        {
-            let file_config = super::file::ConfigFile::load_config(current_dir)
+        let url_str = if config_file.exists() {
+            let file_config = ConfigFile::load_config_file(config_file)
                 .change_context(GetConfigError::LoadFileError)?;
 
             if file_config.tls.is_some() {
@@ -105,19 +213,14 @@ impl ApiClientMode {
         Url::parse(url_str).change_context(GetConfigError::InvalidConstant)
     }
 
-    fn root_certificate_file(&self) -> Result<Option<PathBuf>, GetConfigError> {
+    fn root_certificate_file(&self, config_file: &Path) -> Result<Option<PathBuf>, GetConfigError> {
         if let Some(root_certificate) = self.root_certificate.clone() {
             return Ok(Some(root_certificate));
         }
 
-        let current_dir = std::env::current_dir().change_context(GetConfigError::GetWorkingDir)?;
-
-        if ConfigFile::exists(&current_dir)
-            .change_context(GetConfigError::CheckConfigFileExistanceError)?
-        {
-            let file_config =
-                super::file::ConfigFile::save_default_if_not_exist_and_load(current_dir)
-                    .change_context(GetConfigError::LoadFileError)?;
+        if config_file.exists() {
+            let file_config = ConfigFile::load_config_file(config_file)
+                .change_context(GetConfigError::LoadFileError)?;
 
             Ok(file_config.tls.map(|v| v.root_certificate))
         } else {
@@ -125,13 +228,33 @@ impl ApiClientMode {
         }
     }
 
-    pub fn root_certificate(&self) -> Result<Option<Certificate>, GetConfigError> {
-        if let Some(root_certificate_file) = self.root_certificate_file()? {
-            let cert = load_root_certificate(&root_certificate_file)
-                .change_context(GetConfigError::ReadCertificateError)?;
-            Ok(Some(cert))
+    pub fn root_certificates(
+        &self,
+        config_file: &Path,
+    ) -> Result<Vec<Certificate>, GetConfigError> {
+        if let Some(root_certificate_file) = self.root_certificate_file(config_file)? {
+            load_root_certificates(&root_certificate_file)
+                .change_context(GetConfigError::ReadCertificateError)
         } else {
-            Ok(None)
+            Ok(vec![])
+        }
+    }
+
+    pub fn use_system_root_certificates(&self, config_file: &Path) -> Result<bool, GetConfigError> {
+        if self.system_root_certificates {
+            return Ok(true);
+        }
+
+        if config_file.exists() {
+            let file_config = ConfigFile::load_config_file(config_file)
+                .change_context(GetConfigError::LoadFileError)?;
+
+            Ok(file_config
+                .tls
+                .and_then(|tls| tls.use_system_root_certificates)
+                .unwrap_or(false))
+        } else {
+            Ok(false)
         }
     }
 }
@@ -161,7 +284,42 @@ pub enum ApiCommand {
         #[arg(long)]
         reset_data: bool,
     },
-    SystemInfoAll,
-    SystemInfo,
+    SystemInfoAll {
+        /// Comma separated list of sections. Default is all sections.
+        #[arg(long, value_enum, value_delimiter = ',')]
+        sections: Vec<SystemInfoSection>,
+    },
+    SystemInfo {
+        /// Comma separated list of sections. Default is all sections.
+        #[arg(long, value_enum, value_delimiter = ',')]
+        sections: Vec<SystemInfoSection>,
+    },
     SoftwareInfo,
+    /// Print backend process status
+    BackendStatus,
+    /// Print software update progress
+    UpdateStatus,
+    /// Print automatic reboot status
+    RebootStatus,
+    /// Reboot computer at the next reboot check
+    ScheduleReboot,
+    /// Reboot computer now
+    RebootNow,
+    /// Cancel pending reboot
+    CancelReboot,
+    /// Print journal logs of a service from system info log_services config
+    Logs {
+        service: String,
+        /// Keep printing new log lines
+        #[arg(short, long)]
+        follow: bool,
+        /// Journal priority filter. For example "warning" or "err..warning"
+        #[arg(short, long)]
+        priority: Option<String>,
+        /// Show logs since this time. Uses journalctl time format
+        #[arg(short, long)]
+        since: Option<String>,
+    },
+    /// Reload manager config file without restarting the manager
+    ReloadConfig,
 }
@@ -0,0 +1,680 @@
+//! Semantic config file validation for check-config subcommand
+
+use std::{collections::HashSet, path::Path};
+
+use error_stack::{Result, ResultExt};
+use url::Url;
+
+use super::{
+    file::{BackendControllerType, ConfigFile, CustomCommand, TlsConfig},
+    load_public_api_tls_config, load_root_certificates, GetConfigError, ScriptLocations,
+};
+
+/// Load config file and print all found problems.
+///
+/// Returns false if problems were found.
+pub fn handle_check_config_mode(file_path: &Path) -> Result<bool, GetConfigError> {
+    let file =
+        ConfigFile::load_config_file(file_path).change_context(GetConfigError::LoadFileError)?;
+
+    let (problems, warnings) = check_config(&file);
+    for warning in &warnings {
+        println!("Warning: {}", warning);
+    }
+
+    if problems.is_empty() {
+        println!("Config file {} is valid", file_path.display());
+        Ok(true)
+    } else {
+        for problem in &problems {
+            println!("{}", problem);
+        }
+        println!(
+            "Config file {} has {} problem(s)",
+            file_path.display(),
+            problems.len()
+        );
+        Ok(false)
+    }
+}
+
+/// Returns all config problems which deserialization does not detect
+/// and warnings which do not prevent using the config.
+pub fn check_config(file: &ConfigFile) -> (Vec<String>, Vec<String>) {
+    let mut checker = ConfigChecker::default();
+    checker.check(file);
+    (checker.problems, checker.warnings)
+}
+
+#[derive(Default)]
+struct ConfigChecker {
+    problems: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl ConfigChecker {
+    fn check(&mut self, file: &ConfigFile) {
+        let debug = file.debug.unwrap_or_default();
+
+        if file.api_key.as_str().trim().is_empty() {
+            self.problem("api_key", "API key is empty");
+        }
+
+        for (field, secret) in file.secrets() {
+            if let Some(warning) = secret.permission_warning() {
+                self.warning(field, warning);
+            }
+        }
+
+        // Missing scripts are allowed in debug mode.
+        if !debug {
+            for missing in ScriptLocations::new(&file.scripts_dir).missing_scripts() {
+                self.problem("scripts_dir", missing);
+            }
+        }
+
+        if let Some(port) = file.socket.second_public_api_localhost_only_port {
+            if port == file.socket.public_api.port() {
+                self.problem(
+                    "socket.second_public_api_localhost_only_port",
+                    "Port is the same as public_api port",
+                );
+            }
+        }
+
+        let mut key_names = HashSet::new();
+        for key in file.server_encryption_keys.iter().flatten() {
+            if !key_names.insert(key.name.as_str()) {
+                self.problem(
+                    "server_encryption_keys",
+                    format!("Duplicate key name: {}", key.name),
+                );
+            }
+            self.file_exists("server_encryption_keys.key_path", &key.key_path);
+        }
+
+        if let Some(storage) = &file.secure_storage {
+            self.manager_url("secure_storage.manager_base_url", &storage.manager_base_url);
+            if storage.encryption_key_name.trim().is_empty() {
+                self.problem("secure_storage.encryption_key_name", "Key name is empty");
+            }
+        }
+
+        if file.software_update_provider.is_some() && file.software_builder.is_some() {
+            self.problem(
+                "software_update_provider",
+                "Only one of software_update_provider and software_builder can be configured",
+            );
+        }
+
+        if let Some(update) = &file.software_update_provider {
+            self.manager_url(
+                "software_update_provider.manager_base_url",
+                &update.manager_base_url,
+            );
+            if let Some(path) = &update.binary_decrypting_key_path {
+                self.file_exists("software_update_provider.binary_decrypting_key_path", path);
+            }
+            self.parent_dir_exists(
+                "software_update_provider.manager_install_location",
+                &update.manager_install_location,
+            );
+            self.parent_dir_exists(
+                "software_update_provider.backend_install_location",
+                &update.backend_install_location,
+            );
+            match update.backend_controller {
+                None => {
+                    if update.backend_supervisor.is_some() && update.backend_systemd_unit.is_some()
+                    {
+                        self.problem(
+                            "software_update_provider.backend_controller",
+                            "Both backend_supervisor and backend_systemd_unit are configured, so backend_controller must be configured",
+                        );
+                    }
+                }
+                Some(BackendControllerType::Process) => {
+                    if update.backend_supervisor.is_none() {
+                        self.problem(
+                            "software_update_provider.backend_controller",
+                            "Process controller requires backend_supervisor config",
+                        );
+                    }
+                }
+                Some(BackendControllerType::Systemd) => {
+                    if update.backend_systemd_unit.is_none() {
+                        self.problem(
+                            "software_update_provider.backend_controller",
+                            "Systemd controller requires backend_systemd_unit config",
+                        );
+                    }
+                }
+                Some(BackendControllerType::Script) => (),
+            }
+            if let Some(unit) = &update.backend_systemd_unit {
+                if unit.trim().is_empty() {
+                    self.problem(
+                        "software_update_provider.backend_systemd_unit",
+                        "Unit name is empty",
+                    );
+                }
+            }
+            if let Some(supervisor) = &update.backend_supervisor {
+                if let Some(dir) = &supervisor.working_dir {
+                    if !dir.is_dir() {
+                        self.warning(
+                            "software_update_provider.backend_supervisor.working_dir",
+                            format!("Directory {} does not exist", dir.display()),
+                        );
+                    }
+                }
+                if supervisor.restart_min_delay() > supervisor.restart_max_delay() {
+                    self.problem(
+                        "software_update_provider.backend_supervisor.restart_min_delay_seconds",
+                        "Value must not be greater than restart_max_delay_seconds",
+                    );
+                }
+            }
+            if let Some(url) = update
+                .backend_stop
+                .as_ref()
+                .and_then(|stop| stop.drain_url.as_ref())
+            {
+                self.manager_url("software_update_provider.backend_stop.drain_url", url);
+            }
+        }
+
+        if let Some(builder) = &file.software_builder {
+            let optional_files = [
+                (
+                    "software_builder.manager_download_key_path",
+                    &builder.manager_download_key_path,
+                ),
+                (
+                    "software_builder.manager_pre_build_script",
+                    &builder.manager_pre_build_script,
+                ),
+                (
+                    "software_builder.backend_download_key_path",
+                    &builder.backend_download_key_path,
+                ),
+                (
+                    "software_builder.backend_pre_build_script",
+                    &builder.backend_pre_build_script,
+                ),
+            ];
+            for (field, path) in optional_files {
+                if let Some(path) = path {
+                    self.file_exists(field, path);
+                }
+            }
+            let required_values = [
+                (
+                    "software_builder.manager_download_git_address",
+                    &builder.manager_download_git_address,
+                ),
+                ("software_builder.manager_branch", &builder.manager_branch),
+                ("software_builder.manager_binary", &builder.manager_binary),
+                (
+                    "software_builder.backend_download_git_address",
+                    &builder.backend_download_git_address,
+                ),
+                ("software_builder.backend_branch", &builder.backend_branch),
+                ("software_builder.backend_binary", &builder.backend_binary),
+            ];
+            for (field, value) in required_values {
+                if value.trim().is_empty() {
+                    self.problem(field, "Value is empty");
+                }
+            }
+        }
+
+        if let Some(reboot) = &file.reboot_if_needed {
+            if reboot.time.is_none() && reboot.maintenance_windows.is_empty() {
+                self.problem(
+                    "reboot_if_needed",
+                    "Time or at least one maintenance window is required",
+                );
+            }
+            if let Some(coordination) = &reboot.coordination {
+                self.manager_url(
+                    "reboot_if_needed.coordination.manager_base_url",
+                    &coordination.manager_base_url,
+                );
+                if coordination.name.trim().is_empty() {
+                    self.problem("reboot_if_needed.coordination.name", "Name is empty");
+                }
+            }
+            for window in &reboot.maintenance_windows {
+                if window.length().is_zero() {
+                    self.problem(
+                        "reboot_if_needed.maintenance_windows",
+                        "Start and end must be different",
+                    );
+                }
+                if window.time_zone.is_some() && window.utc_offset.is_some() {
+                    self.problem(
+                        "reboot_if_needed.maintenance_windows",
+                        "Time zone and UTC offset can not be used together",
+                    );
+                }
+                if window.weekdays.as_ref().is_some_and(|days| days.is_empty()) {
+                    self.problem(
+                        "reboot_if_needed.maintenance_windows.weekdays",
+                        "At least one weekday is required",
+                    );
+                }
+            }
+        }
+
+        if let Some(coordinator) = &file.reboot_coordinator {
+            if coordinator.max_concurrent_reboots == Some(0) {
+                self.problem(
+                    "reboot_coordinator.max_concurrent_reboots",
+                    "Value must be greater than zero",
+                );
+            }
+            if coordinator.lease_timeout_seconds == Some(0) {
+                self.problem(
+                    "reboot_coordinator.lease_timeout_seconds",
+                    "Value must be greater than zero",
+                );
+            }
+        }
+
+        if let Some(hooks) = &file.hooks {
+            let pre_reboot = hooks.pre_reboot.iter().map(|hook| &hook.command);
+            self.custom_commands("hooks.pre_reboot", pre_reboot);
+            self.custom_commands("hooks.post_boot", hooks.post_boot.iter());
+        }
+
+        if let Some(info) = &file.system_info {
+            if info.remote_timeout_seconds == Some(0) {
+                self.problem(
+                    "system_info.remote_timeout_seconds",
+                    "Value must be greater than zero",
+                );
+            }
+            for service in &info.log_services {
+                if service.name().trim().is_empty() {
+                    self.problem("system_info.log_services", "Service name is empty");
+                }
+                let Some(options) = service.options() else {
+                    continue;
+                };
+                if options.lines == Some(0) {
+                    self.problem(
+                        "system_info.log_services.lines",
+                        "Value must be greater than zero",
+                    );
+                }
+                if let Some(priority) = &options.priority {
+                    if !is_valid_journal_priority(priority) {
+                        self.problem(
+                            "system_info.log_services.priority",
+                            format!("Invalid priority: {}", priority),
+                        );
+                    }
+                }
+            }
+            self.custom_commands("system_info.commands", info.commands.iter().flatten());
+            let mut manager_names = HashSet::new();
+            for manager in info.remote_managers.iter().flatten() {
+                if !manager_names.insert(manager.name.as_str()) {
+                    self.problem(
+                        "system_info.remote_managers",
+                        format!("Duplicate manager name: {}", manager.name),
+                    );
+                }
+                self.manager_url(
+                    "system_info.remote_managers.manager_base_url",
+                    &manager.manager_base_url,
+                );
+            }
+        }
+
+        if let Some(logging) = &file.logging {
+            if let Some(level) = &logging.level {
+                if let Err(e) = tracing_subscriber::EnvFilter::try_new(level) {
+                    self.problem("logging.level", format!("Invalid log level: {}", e));
+                }
+            }
+            if logging
+                .file
+                .as_ref()
+                .is_some_and(|f| f.max_files == Some(0))
+            {
+                self.problem("logging.file.max_files", "Value must be greater than zero");
+            }
+        }
+
+        match &file.tls {
+            Some(tls) => self.check_tls(tls, &file.storage_dir),
+            None if !debug => self.problem("tls", "TLS config is required when debug mode is off"),
+            None => (),
+        }
+    }
+
+    fn check_tls(&mut self, tls: &TlsConfig, storage_dir: &Path) {
+        match &tls.acme {
+            Some(acme) => {
+                if tls.public_api_cert.is_some() || tls.public_api_key.is_some() {
+                    self.problem(
+                        "tls.acme",
+                        "public_api_cert and public_api_key are not used when ACME is enabled",
+                    );
+                }
+                if acme.domains.is_empty() {
+                    self.problem("tls.acme.domains", "At least one domain is required");
+                }
+                if acme.directory_url.scheme() != "https" {
+                    self.problem("tls.acme.directory_url", "HTTPS URL is required");
+                }
+                if acme.renew_before_expiry_days == Some(0) {
+                    self.problem(
+                        "tls.acme.renew_before_expiry_days",
+                        "Value must be greater than zero",
+                    );
+                }
+                if let Some(ca_certificate) = &acme.ca_certificate {
+                    if self.file_exists("tls.acme.ca_certificate", ca_certificate) {
+                        if let Err(e) = load_root_certificates(ca_certificate) {
+                            self.problem(
+                                "tls.acme.ca_certificate",
+                                format!("Loading certificates failed: {:?}", e),
+                            );
+                        }
+                    }
+                }
+            }
+            None => match (&tls.public_api_cert, &tls.public_api_key) {
+                (Some(cert), Some(key)) => {
+                    let cert_exists = self.file_exists("tls.public_api_cert", cert);
+                    let key_exists = self.file_exists("tls.public_api_key", key);
+                    if cert_exists && key_exists {
+                        if let Err(e) = load_public_api_tls_config(tls, storage_dir) {
+                            self.problem("tls", format!("Loading certificate failed: {:?}", e));
+                        }
+                    }
+                }
+                _ => self.problem(
+                    "tls",
+                    "public_api_cert and public_api_key are required when ACME is disabled",
+                ),
+            },
+        }
+
+        if self.file_exists("tls.root_certificate", &tls.root_certificate) {
+            if let Err(e) = load_root_certificates(&tls.root_certificate) {
+                self.problem(
+                    "tls.root_certificate",
+                    format!("Loading certificates failed: {:?}", e),
+                );
+            }
+        }
+    }
+
+    fn custom_commands<'a>(
+        &mut self,
+        field: &str,
+        commands: impl Iterator<Item = &'a CustomCommand>,
+    ) {
+        let mut command_names = HashSet::new();
+        for command in commands {
+            if !command_names.insert(command.name.as_str()) {
+                self.problem(field, format!("Duplicate command name: {}", command.name));
+            }
+            if command.command.trim().is_empty() {
+                self.problem(&format!("{}.command", field), "Command is empty");
+            }
+            if command.timeout_seconds == Some(0) {
+                self.problem(
+                    &format!("{}.timeout_seconds", field),
+                    "Value must be greater than zero",
+                );
+            }
+        }
+    }
+
+    fn manager_url(&mut self, field: &str, url: &Url) {
+        if url.scheme() != "http" && url.scheme() != "https" {
+            self.problem(field, format!("Unsupported URL scheme: {}", url));
+        }
+        if url.host().is_none() {
+            self.problem(field, format!("URL host is missing: {}", url));
+        }
+    }
+
+    /// Returns true if file exists.
+    fn file_exists(&mut self, field: &str, path: &Path) -> bool {
+        if path.is_file() {
+            true
+        } else {
+            self.problem(field, format!("File not found: {}", path.display()));
+            false
+        }
+    }
+
+    fn parent_dir_exists(&mut self, field: &str, path: &Path) {
+        match path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() || parent.is_dir() => (),
+            _ => self.problem(
+                field,
+                format!("Parent directory not found: {}", path.display()),
+            ),
+        }
+    }
+
+    fn warning(&mut self, field: &str, message: impl Into<String>) {
+        self.warnings.push(format!("{}: {}", field, message.into()));
+    }
+
+    fn problem(&mut self, field: &str, message: impl Into<String>) {
+        self.problems.push(format!("{}: {}", field, message.into()));
+    }
+}
+
+/// Priority is a name, a number or a range like "err..warning".
+pub fn is_valid_journal_priority(priority: &str) -> bool {
+    const PRIORITIES: &[&str] = &[
+        "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+    ];
+    priority.split("..").all(|value| {
+        PRIORITIES.contains(&value) || value.parse::<u8>().is_ok_and(|value| value <= 7)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(dir: &Path, extra_config: &str) -> Vec<String> {
+        let config_text = format!(
+            r#"
+debug = true
+api_key = "password"
+storage_dir = "{}"
+scripts_dir = "{}"
+
+[socket]
+public_api = "127.0.0.1:5000"
+
+{}
+"#,
+            dir.display(),
+            dir.display(),
+            extra_config,
+        );
+        let file: ConfigFile = toml::from_str(&config_text).unwrap();
+        check_config(&file).0
+    }
+
+    fn has_problem(problems: &[String], field: &str) -> bool {
+        let prefix = format!("{}: ", field);
+        problems.iter().any(|problem| problem.starts_with(&prefix))
+    }
+
+    const BUILDER: &str = r#"
+[software_builder]
+manager_download_git_address = "https://example.com/manager.git"
+manager_branch = "main"
+manager_binary = "app-manager"
+backend_download_git_address = "https://example.com/backend.git"
+backend_branch = "main"
+backend_binary = "app-backend"
+"#;
+
+    fn update_provider(dir: &Path) -> String {
+        format!(
+            r#"
+[software_update_provider]
+manager_base_url = "https://example.com:5000"
+manager_install_location = "{0}/app-manager"
+backend_install_location = "{0}/app-backend"
+"#,
+            dir.display()
+        )
+    }
+
+    #[test]
+    fn minimal_debug_config_is_valid() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(check(dir.path(), ""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn software_builder_and_update_provider_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let update = update_provider(dir.path());
+
+        assert!(check(dir.path(), BUILDER).is_empty());
+        assert!(check(dir.path(), &update).is_empty());
+        let problems = check(dir.path(), &format!("{}{}", update, BUILDER));
+        assert!(has_problem(&problems, "software_update_provider"));
+    }
+
+    #[test]
+    fn unsupported_url_scheme_is_problem() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = r#"
+[secure_storage]
+manager_base_url = "ftp://example.com"
+encryption_key_name = "key"
+availability_check_path = "/"
+"#;
+
+        let problems = check(dir.path(), config);
+        assert!(has_problem(&problems, "secure_storage.manager_base_url"));
+        let problems = check(dir.path(), &config.replace("ftp://", "https://"));
+        assert!(problems.is_empty());
+    }
+
+    #[test]
+    fn missing_key_file_is_problem() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_path = dir.path().join("server.key");
+        let config = format!(
+            "[[server_encryption_keys]]\nname = \"key\"\nkey_path = \"{}\"\n",
+            key_path.display()
+        );
+
+        let problems = check(dir.path(), &config);
+        assert!(has_problem(&problems, "server_encryption_keys.key_path"));
+        std::fs::write(&key_path, "key").unwrap();
+        assert!(check(dir.path(), &config).is_empty());
+    }
+
+    #[test]
+    fn zero_values_are_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = r#"
+[reboot_coordinator]
+max_concurrent_reboots = 0
+lease_timeout_seconds = 0
+
+[system_info]
+remote_timeout_seconds = 0
+log_services = [{ name = "app-backend", lines = 0 }]
+
+[[system_info.commands]]
+name = "uptime"
+command = "uptime"
+timeout_seconds = 0
+
+[logging.file]
+max_files = 0
+"#;
+
+        let problems = check(dir.path(), config);
+        for field in [
+            "reboot_coordinator.max_concurrent_reboots",
+            "reboot_coordinator.lease_timeout_seconds",
+            "system_info.remote_timeout_seconds",
+            "system_info.log_services.lines",
+            "system_info.commands.timeout_seconds",
+            "logging.file.max_files",
+        ] {
+            assert!(has_problem(&problems, field), "{} not found", field);
+        }
+        assert_eq!(problems.len(), 6);
+    }
+
+    #[test]
+    fn journal_priority_validation() {
+        for priority in ["err", "3", "0", "7", "err..warning", "0..debug"] {
+            assert!(is_valid_journal_priority(priority), "{}", priority);
+        }
+        for priority in ["8", "foo", "", "err..foo", "-1", "err..warning..info.."] {
+            assert!(!is_valid_journal_priority(priority), "{}", priority);
+        }
+    }
+
+    #[test]
+    fn tls_is_required_without_debug_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let problems = check(dir.path(), "");
+        assert!(!has_problem(&problems, "tls"));
+
+        let config_text = format!(
+            "api_key = \"password\"\nstorage_dir = \"{0}\"\nscripts_dir = \"{0}\"\n\
+            [socket]\npublic_api = \"127.0.0.1:5000\"\n",
+            dir.path().display()
+        );
+        let file: ConfigFile = toml::from_str(&config_text).unwrap();
+        assert!(has_problem(&check_config(&file).0, "tls"));
+    }
+
+    #[test]
+    fn acme_and_certificate_files_are_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let tls = format!(
+            "[tls]\nroot_certificate = \"{}/root.crt\"\n",
+            dir.path().display()
+        );
+        let acme = r#"
+[tls.acme]
+directory_url = "https://acme.example.com/directory"
+domains = ["example.com"]
+"#;
+        let cert_and_key = "public_api_cert = \"server.crt\"\npublic_api_key = \"server.key\"\n";
+
+        let problems = check(dir.path(), &tls);
+        assert!(has_problem(&problems, "tls"));
+
+        let problems = check(dir.path(), &format!("{}{}", tls, cert_and_key));
+        assert!(!has_problem(&problems, "tls"));
+        assert!(has_problem(&problems, "tls.public_api_cert"));
+        assert!(has_problem(&problems, "tls.public_api_key"));
+
+        let problems = check(dir.path(), &format!("{}{}", tls, acme));
+        assert!(!has_problem(&problems, "tls"));
+        assert!(!has_problem(&problems, "tls.acme"));
+
+        let problems = check(dir.path(), &format!("{}{}{}", tls, cert_and_key, acme));
+        assert!(has_problem(&problems, "tls.acme"));
+
+        let http_acme = acme.replace("https://", "http://");
+        let problems = check(dir.path(), &format!("{}{}", tls, http_acme));
+        assert!(has_problem(&problems, "tls.acme.directory_url"));
+    }
+}
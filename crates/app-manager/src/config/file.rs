@@ -1,30 +1,68 @@
 use std::{
-    io::Write,
     net::SocketAddr,
+    os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use error_stack::{Report, Result, ResultExt};
 use manager_model::DataEncryptionKey;
 use serde::{Deserialize, Serialize};
+use time::{Date, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset, Weekday};
+use time_tz::{OffsetDateTimeExt, OffsetResult, PrimitiveDateTimeExt, Tz};
 use url::Url;
 
 use super::GetConfigError;
 
 pub const CONFIG_FILE_NAME: &str = "manager_config.toml";
 
+/// Example config file with all available options. The init subcommand
+/// can print this.
 pub const DEFAULT_CONFIG_FILE_TEXT: &str = r#"
 
+# Some values can be overridden using environment variables:
+# APP_MANAGER_API_KEY, APP_MANAGER_DEBUG, APP_MANAGER_STORAGE_DIR,
+# APP_MANAGER_SCRIPTS_DIR, APP_MANAGER_LOG_TIMESTAMP,
+# APP_MANAGER_SOCKET_PUBLIC_API,
+# APP_MANAGER_SOCKET_SECOND_PUBLIC_API_LOCALHOST_ONLY_PORT,
+# APP_MANAGER_TLS_PUBLIC_API_CERT, APP_MANAGER_TLS_PUBLIC_API_KEY and
+# APP_MANAGER_TLS_ROOT_CERTIFICATE.
+
+# Secrets (api_key and encryption_key_text) can be also read from a file
+# or an environment variable when the config is loaded:
+# api_key = { file = "/etc/app-manager/api_key" }
+# api_key = { env = "API_KEY" }
+
+# Sections server_encryption_keys, reboot_if_needed and system_info
+# are reloaded when SIGHUP is received or when config reload is
+# requested using the manager API. Other changes require restart.
+
 # Required
+# Manager API is locked if a request with wrong API key is received.
+# Stop the manager, run 'app-manager unlock-api' and start the manager
+# to unlock the API. If storage_dir is on secure storage, mount it before
+# running unlock-api.
 # api_key = "password"
 # scripts_dir = "/app-server-tools/manager-tools"
 # storage_dir = "/app-secure-storage/app/app-manager-storage"
 
 # log_timestamp = true # optional
 
+# [logging]
+# format = "text" # Optional. Value "text" or "json".
+# level = "info" # Optional. RUST_LOG environment variable overrides this.
+# journald = false # Optional
+# Optional. Write logs also to rotating log files.
+# [logging.file]
+# dir = "/var/log/app-manager" # Optional. Default is "logs" in storage_dir.
+# rotation = "daily" # Optional. Value "minutely", "hourly", "daily" or "never".
+# max_files = 7 # Optional
+
 [socket]
 public_api = "127.0.0.1:5000"
-# Second API has no TLS even if it is configured
+# Second API has no TLS even if it is configured. Prometheus metrics
+# are available from /metrics. API key is required for metrics only
+# when using public_api.
 # second_public_api_localhost_only_port = 5001
 
 # [secure_storage]
@@ -46,6 +84,39 @@ public_api = "127.0.0.1:5000"
 # manager_install_location = "/home/app/binaries/app-manager"
 # backend_install_location = "/app-secure-storage/app/binaries/app-backend"
 # backend_data_reset_dir = "/path/to/backend/data" # Optional
+# Optional. After manager update, restart the manager using the new binary
+# instead of rebooting. Secure storage stays mounted and the backend keeps
+# running unless it is a child process of the manager (backend_supervisor).
+# manager_self_update = false
+# Optional. Possible values are "script", "process" and "systemd". Script
+# mode uses start-backend.sh and stop-backend.sh scripts. Default is selected
+# using backend_supervisor and backend_systemd_unit config.
+# backend_controller = "script"
+# Optional. Control the backend systemd unit using systemd D-Bus API instead
+# of start-backend.sh and stop-backend.sh scripts. The manager user must
+# have permission to manage the unit, for example using a polkit rule.
+# backend_systemd_unit = "app-backend.service"
+# Optional. Run the backend binary as a child process of the manager
+# instead of using start-backend.sh and stop-backend.sh scripts. The
+# backend is restarted with increasing delay if it quits unexpectedly.
+# [software_update_provider.backend_supervisor]
+# args = [] # Optional
+# working_dir = "/app-secure-storage/app" # Optional. Default is the binary directory.
+# restart_min_delay_seconds = 1 # Optional
+# restart_max_delay_seconds = 300 # Optional
+# stop_timeout_seconds = 10 # Optional
+# Optional. Backend stop sequence which is used when the backend is
+# restarted and when the manager quits. The drain hook HTTP POST request is
+# sent first and the manager waits for the response. After that the backend
+# receives SIGTERM and it is killed if it does not quit before the stop
+# timeout. With systemd the unit's stop timeout is used instead. In script
+# mode the stop timeout is given to stop-backend.sh as the first argument
+# and the script must kill the backend. Without stop_timeout_seconds the
+# stop script has no timeout.
+# [software_update_provider.backend_stop]
+# drain_url = "http://127.0.0.1:3000/drain" # Optional
+# drain_timeout_seconds = 30 # Optional
+# stop_timeout_seconds = 10 # Optional
 
 # [software_builder]
 # manager_download_key_path = "app-manager-ssh.key" # Optional
@@ -60,28 +131,191 @@ public_api = "127.0.0.1:5000"
 # backend_pre_build_script = "/app-secure-storage/app/app-manager-storage/build/backend/manager-pre-build-script.sh" # Optional
 
 # [reboot_if_needed]
-# time = "12:00"
+# time = "12:00" # Optional. UTC time when reboot is checked once per day.
+#
+# Optional. Reboot is allowed anywhere inside a maintenance window. Failed
+# reboot is retried inside the same window. If end is before start, the
+# window ends on the next day.
+# [[reboot_if_needed.maintenance_windows]]
+# weekdays = ["sat", "sun"] # Optional. Default is every day.
+# start = "02:00"
+# end = "05:00"
+# time_zone = "Europe/Helsinki" # Optional. IANA time zone name. Daylight saving time is supported.
+# utc_offset = "+02:00" # Optional. Fixed UTC offset. Default is UTC.
+#
+# Optional. Take a reboot lease from the reboot coordinator manager before
+# rebooting. Reboot is retried later if the lease is not granted.
+# [reboot_if_needed.coordination]
+# manager_base_url = "https://coordinator.example.com:5000"
+# name = "server1" # Lease holder name. Must be unique between servers.
+
+# Optional. Grant reboot leases to other managers.
+# [reboot_coordinator]
+# max_concurrent_reboots = 1 # Optional. Default is 1.
+# lease_timeout_seconds = 1800 # Optional. Default is 1800.
+
+# Optional. Commands which are run in the configured order. Pre-reboot
+# hooks run after the reboot lease is acquired. The reboot is cancelled if
+# a hook fails and abort_on_failure is true. Post-boot hooks run once per
+# boot after secure storage is mounted and before the backend starts. Hook
+# outputs are saved to the state file. Options are the same as in
+# system_info.commands.
+# [[hooks.pre_reboot]]
+# name = "notify-backend"
+# command = "curl"
+# args = ["-X", "POST", "http://127.0.0.1:3000/reboot"] # Optional
+# timeout_seconds = 30 # Optional
+# sudo = false # Optional
+# abort_on_failure = true # Optional. Default is true.
+# [[hooks.post_boot]]
+# name = "check-data"
+# command = "/app-server-tools/check-data.sh"
 
 # [system_info]
-# log_services = ["app-manager", "app-backend"]
+# Service name or table with journalctl options. Options lines (default 20),
+# since, until (journalctl time format) and priority (for example "warning")
+# are optional.
+# log_services = ["app-manager", { name = "app-backend", lines = 100, since = "-1h", priority = "warning" }]
+# remote_timeout_seconds = 10 # Optional
 # [[system_info.remote_managers]]
 # name = "test-server"
 # manager_base_url = "http://127.0.0.1:5000"
+# Optional. Commands which output is included in system info.
+# [[system_info.commands]]
+# name = "disk-io"
+# command = "iostat"
+# args = ["-x"] # Optional
+# timeout_seconds = 30 # Optional
+# sudo = false # Optional
 
 # [tls]
+# Certificate and key are reloaded when SIGHUP is received
 # public_api_cert = "tls/server.crt"
 # public_api_key = "tls/server.key"
-# root_certificate = "tls/root.crt"
+# root_certificate = "tls/root.crt" # Can contain multiple certificates
+# use_system_root_certificates = false # Optional
+# Get public API certificate from ACME server instead of using
+# public_api_cert and public_api_key.
+# [tls.acme]
+# directory_url = "https://acme-v02.api.letsencrypt.org/directory"
+# domains = ["example.com"]
+# contact_email = "admin@example.com" # Optional
+# renew_before_expiry_days = 30 # Optional
+# http_challenge_port = 80 # Optional
+# Optional. Trust this CA certificate when connecting to the ACME server.
+# For example Pebble test server CA. Operating system root certificates
+# are also trusted.
+# ca_certificate = "tls/acme-ca.crt"
 "#;
 
 #[derive(thiserror::Error, Debug)]
 pub enum ConfigFileError {
-    #[error("Save default")]
-    SaveDefault,
     #[error("Not a directory")]
     NotDirectory,
     #[error("Load config file")]
     LoadConfig,
+    #[error("Invalid environment variable value")]
+    EnvOverride,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EnvValueType {
+    String,
+    Bool,
+    Integer,
+}
+
+/// Environment variables which override config file values. Missing
+/// tables are created.
+const ENV_OVERRIDES: &[(&str, &[&str], EnvValueType)] = &[
+    ("APP_MANAGER_API_KEY", &["api_key"], EnvValueType::String),
+    ("APP_MANAGER_DEBUG", &["debug"], EnvValueType::Bool),
+    (
+        "APP_MANAGER_STORAGE_DIR",
+        &["storage_dir"],
+        EnvValueType::String,
+    ),
+    (
+        "APP_MANAGER_SCRIPTS_DIR",
+        &["scripts_dir"],
+        EnvValueType::String,
+    ),
+    (
+        "APP_MANAGER_LOG_TIMESTAMP",
+        &["log_timestamp"],
+        EnvValueType::Bool,
+    ),
+    (
+        "APP_MANAGER_SOCKET_PUBLIC_API",
+        &["socket", "public_api"],
+        EnvValueType::String,
+    ),
+    (
+        "APP_MANAGER_SOCKET_SECOND_PUBLIC_API_LOCALHOST_ONLY_PORT",
+        &["socket", "second_public_api_localhost_only_port"],
+        EnvValueType::Integer,
+    ),
+    (
+        "APP_MANAGER_TLS_PUBLIC_API_CERT",
+        &["tls", "public_api_cert"],
+        EnvValueType::String,
+    ),
+    (
+        "APP_MANAGER_TLS_PUBLIC_API_KEY",
+        &["tls", "public_api_key"],
+        EnvValueType::String,
+    ),
+    (
+        "APP_MANAGER_TLS_ROOT_CERTIFICATE",
+        &["tls", "root_certificate"],
+        EnvValueType::String,
+    ),
+];
+
+fn apply_env_overrides(config: &mut toml::Table) -> Result<(), ConfigFileError> {
+    for (name, path, value_type) in ENV_OVERRIDES {
+        let value = match std::env::var(name) {
+            Ok(value) => value,
+            Err(std::env::VarError::NotPresent) => continue,
+            Err(e) => {
+                return Err(e)
+                    .change_context(ConfigFileError::EnvOverride)
+                    .attach_printable(*name)
+            }
+        };
+
+        let value = match value_type {
+            EnvValueType::String => toml::Value::String(value),
+            EnvValueType::Bool => toml::Value::Boolean(
+                value
+                    .parse::<bool>()
+                    .change_context(ConfigFileError::EnvOverride)
+                    .attach_printable(*name)?,
+            ),
+            EnvValueType::Integer => toml::Value::Integer(
+                value
+                    .parse::<i64>()
+                    .change_context(ConfigFileError::EnvOverride)
+                    .attach_printable(*name)?,
+            ),
+        };
+
+        let Some((field, tables)) = path.split_last() else {
+            continue;
+        };
+        let mut table = &mut *config;
+        for table_name in tables {
+            table = table
+                .entry(*table_name)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or(ConfigFileError::EnvOverride)
+                .attach_printable_lazy(|| format!("{}: {} is not a table", name, table_name))?;
+        }
+        table.insert(field.to_string(), value);
+    }
+
+    Ok(())
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -89,91 +323,302 @@ pub struct ConfigFile {
     pub debug: Option<bool>,
     /// API key for manager API. All managers instances must use the same key.
     ///
-    /// If the key is wrong the API access is denied untill manager is restarted.
-    pub api_key: String,
+    /// If the key is wrong the API access is denied until the API is
+    /// unlocked with the unlock-api subcommand and manager is restarted.
+    pub api_key: SecretString,
     /// Directory for build and update files.
     pub storage_dir: PathBuf,
     pub scripts_dir: PathBuf,
     pub socket: SocketConfig,
 
     // Optional configs
+    /// Can be changed without restart.
     pub server_encryption_keys: Option<Vec<ServerEncryptionKey>>,
     pub secure_storage: Option<SecureStorageConfig>,
+    /// Can be changed without restart.
     pub reboot_if_needed: Option<RebootIfNeededConfig>,
+    pub reboot_coordinator: Option<RebootCoordinatorConfig>,
+    pub hooks: Option<HooksConfig>,
     pub software_update_provider: Option<SoftwareUpdateProviderConfig>,
     pub software_builder: Option<SoftwareBuilderConfig>,
+    /// Can be changed without restart.
     pub system_info: Option<SystemInfoConfig>,
     /// TLS is required if debug setting is false.
     pub tls: Option<TlsConfig>,
     /// Write timestamp to log messages. Enabled by default.
     pub log_timestamp: Option<bool>,
+    pub logging: Option<LoggingConfig>,
 }
 
 impl ConfigFile {
-    pub fn save_default(dir: impl AsRef<Path>) -> Result<(), ConfigFileError> {
-        let file_path =
-            Self::default_config_file_path(dir).change_context(ConfigFileError::SaveDefault)?;
-        let mut file =
-            std::fs::File::create(file_path).change_context(ConfigFileError::SaveDefault)?;
-        file.write_all(DEFAULT_CONFIG_FILE_TEXT.as_bytes())
-            .change_context(ConfigFileError::SaveDefault)?;
-        Ok(())
+    /// Load config file and apply environment variable overrides.
+    pub fn load_config_file(file_path: impl AsRef<Path>) -> Result<ConfigFile, ConfigFileError> {
+        let config_string = std::fs::read_to_string(file_path.as_ref())
+            .change_context(ConfigFileError::LoadConfig)
+            .attach_printable_lazy(|| file_path.as_ref().display().to_string())?;
+        let mut config: toml::Table =
+            toml::from_str(&config_string).change_context(ConfigFileError::LoadConfig)?;
+        apply_env_overrides(&mut config)?;
+        toml::Value::Table(config)
+            .try_into()
+            .change_context(ConfigFileError::LoadConfig)
     }
 
-    pub fn save_default_if_not_exist_and_load(
-        dir: impl AsRef<Path>,
-    ) -> Result<ConfigFile, ConfigFileError> {
-        Self::load(dir, true)
+    pub fn default_config_file_path(dir: impl AsRef<Path>) -> Result<PathBuf, ConfigFileError> {
+        if !dir.as_ref().is_dir() {
+            return Err(Report::new(ConfigFileError::NotDirectory));
+        }
+        let mut file_path = dir.as_ref().to_path_buf();
+        file_path.push(CONFIG_FILE_NAME);
+        Ok(file_path)
+    }
+
+    /// Secret values with config file field names.
+    pub fn secrets(&self) -> Vec<(&'static str, &SecretString)> {
+        let mut secrets = vec![("api_key", &self.api_key)];
+        if let Some(text) = self
+            .secure_storage
+            .as_ref()
+            .and_then(|s| s.encryption_key_text.as_ref())
+        {
+            secrets.push(("secure_storage.encryption_key_text", text));
+        }
+        secrets
     }
+}
+
+/// Location of secret value in config file.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SecretSource {
+    Value(String),
+    File { file: PathBuf },
+    Env { env: String },
+}
 
-    pub fn load_config(dir: impl AsRef<Path>) -> Result<ConfigFile, ConfigFileError> {
-        Self::load(dir, false)
+/// Secret value which is resolved when config file is loaded.
+///
+/// Config file value can be a string, `{ file = "/path" }` or
+/// `{ env = "VAR" }`. Trailing line break is removed from file contents.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(try_from = "SecretSource", into = "SecretSource")]
+pub struct SecretString {
+    value: String,
+    source: SecretSource,
+}
+
+impl SecretString {
+    pub fn as_str(&self) -> &str {
+        &self.value
     }
 
-    fn load(dir: impl AsRef<Path>, save_default: bool) -> Result<ConfigFile, ConfigFileError> {
-        let file_path =
-            Self::default_config_file_path(&dir).change_context(ConfigFileError::LoadConfig)?;
-        if !file_path.exists() && save_default {
-            Self::save_default(dir).change_context(ConfigFileError::LoadConfig)?;
+    /// Returns warning message if the secret is read from a file which
+    /// other users can read.
+    pub fn permission_warning(&self) -> Option<String> {
+        let SecretSource::File { file } = &self.source else {
+            return None;
+        };
+        match std::fs::metadata(file) {
+            Ok(metadata) if metadata.permissions().mode() & 0o004 != 0 => {
+                Some(format!("Secret file {} is world-readable", file.display()))
+            }
+            _ => None,
         }
+    }
+}
+
+impl TryFrom<SecretSource> for SecretString {
+    type Error = String;
+    fn try_from(source: SecretSource) -> std::result::Result<Self, Self::Error> {
+        let value = match &source {
+            SecretSource::Value(value) => value.clone(),
+            SecretSource::File { file } => std::fs::read_to_string(file)
+                .map_err(|e| format!("Reading secret file {} failed: {}", file.display(), e))?
+                .trim_end_matches(['\n', '\r'])
+                .to_string(),
+            SecretSource::Env { env } => std::env::var(env).map_err(|e| {
+                format!("Reading secret environment variable {} failed: {}", env, e)
+            })?,
+        };
 
-        let config_string =
-            std::fs::read_to_string(file_path).change_context(ConfigFileError::LoadConfig)?;
-        toml::from_str(&config_string).change_context(ConfigFileError::LoadConfig)
+        Ok(Self { value, source })
     }
+}
 
-    pub fn default_config_file_path(dir: impl AsRef<Path>) -> Result<PathBuf, ConfigFileError> {
-        if !dir.as_ref().is_dir() {
-            return Err(Report::new(ConfigFileError::NotDirectory));
+impl From<SecretString> for SecretSource {
+    fn from(value: SecretString) -> Self {
+        value.source
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SecretString")
+            .field("source", &self.source.redacted())
+            .finish()
+    }
+}
+
+impl SecretSource {
+    /// Hide inline secret value.
+    fn redacted(&self) -> SecretSource {
+        match self {
+            Self::Value(_) => Self::Value("***".to_string()),
+            other => other.clone(),
         }
-        let mut file_path = dir.as_ref().to_path_buf();
-        file_path.push(CONFIG_FILE_NAME);
-        Ok(file_path)
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct LoggingConfig {
+    /// Optional. Default value is text.
+    pub format: Option<LogFormat>,
+    /// Optional. Log level filter using RUST_LOG syntax. For example
+    /// "info" or "app_manager=debug,warn". If RUST_LOG environment variable
+    /// is set, it is used instead.
+    pub level: Option<String>,
+    /// Optional. Write logs also to rotating log files.
+    pub file: Option<LogFileConfig>,
+    /// Optional. Write logs also to journald. Default value is false.
+    pub journald: Option<bool>,
+}
+
+impl LoggingConfig {
+    pub fn format(&self) -> LogFormat {
+        self.format.unwrap_or_default()
+    }
+
+    pub fn journald(&self) -> bool {
+        self.journald.unwrap_or(false)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LogFileConfig {
+    /// Optional. Log file directory. Default value is "logs" directory
+    /// in storage_dir.
+    pub dir: Option<PathBuf>,
+    /// Optional. Default value is daily.
+    pub rotation: Option<LogRotation>,
+    /// Optional. Old log files are deleted when there is more log files
+    /// than this. Default value is 7.
+    pub max_files: Option<usize>,
+}
+
+impl LogFileConfig {
+    pub fn dir(&self, storage_dir: &Path) -> PathBuf {
+        self.dir
+            .clone()
+            .unwrap_or_else(|| storage_dir.join(LOG_DIR_NAME))
+    }
+
+    pub fn rotation(&self) -> LogRotation {
+        self.rotation.unwrap_or_default()
     }
 
-    pub fn exists(dir: impl AsRef<Path>) -> Result<bool, ConfigFileError> {
-        let file_path =
-            Self::default_config_file_path(&dir).change_context(ConfigFileError::LoadConfig)?;
-        Ok(file_path.exists())
+    pub fn max_files(&self) -> usize {
+        self.max_files.unwrap_or(7)
     }
 }
 
+pub const LOG_DIR_NAME: &str = "logs";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRotation {
+    Minutely,
+    Hourly,
+    #[default]
+    Daily,
+    Never,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct SocketConfig {
     pub public_api: SocketAddr,
     pub second_public_api_localhost_only_port: Option<u16>,
 }
 
+/// Public API certificate and key are reloaded from files when
+/// app-manager receives SIGHUP signal.
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TlsConfig {
-    pub public_api_cert: PathBuf,
-    pub public_api_key: PathBuf,
+    /// Required if ACME is not configured.
+    pub public_api_cert: Option<PathBuf>,
+    /// Required if ACME is not configured.
+    pub public_api_key: Option<PathBuf>,
 
     /// Root certificate for HTTP client for checking API calls.
+    ///
+    /// The file can contain multiple certificates, so for example
+    /// both old and new CA certificates can be trusted during CA rotation.
     pub root_certificate: PathBuf,
+
+    /// Optional. Trust also the operating system root certificates when
+    /// making API calls. Default value is false.
+    pub use_system_root_certificates: Option<bool>,
+
+    /// Optional. Get public API certificate from ACME server. Certificate
+    /// and key are stored to storage directory.
+    pub acme: Option<AcmeConfig>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl TlsConfig {
+    /// Returns public API certificate and key file paths.
+    pub fn public_api_cert_and_key(&self, storage_dir: &Path) -> Option<(PathBuf, PathBuf)> {
+        if self.acme.is_some() {
+            let acme_dir = storage_dir.join(ACME_DIR_NAME);
+            Some((
+                acme_dir.join(ACME_CERT_FILE_NAME),
+                acme_dir.join(ACME_KEY_FILE_NAME),
+            ))
+        } else {
+            Some((self.public_api_cert.clone()?, self.public_api_key.clone()?))
+        }
+    }
+}
+
+pub const ACME_DIR_NAME: &str = "acme";
+pub const ACME_CERT_FILE_NAME: &str = "server.crt";
+pub const ACME_KEY_FILE_NAME: &str = "server.key";
+pub const ACME_ACCOUNT_FILE_NAME: &str = "account.json";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AcmeConfig {
+    /// ACME directory URL. For example
+    /// "https://acme-v02.api.letsencrypt.org/directory".
+    pub directory_url: Url,
+    /// Domains for the certificate.
+    pub domains: Vec<String>,
+    /// Optional. Contact email for the ACME account.
+    pub contact_email: Option<String>,
+    /// Optional. Certificate is renewed when it expires in less than this.
+    /// Default value is 30 days.
+    pub renew_before_expiry_days: Option<u32>,
+    /// Optional. HTTP-01 challenge responses are served from the public API.
+    /// If the public API is not reachable with plain HTTP, responses can
+    /// be served also from this port (for example 80) without TLS.
+    pub http_challenge_port: Option<u16>,
+    /// Optional. CA certificate file for ACME server connections. The file
+    /// can contain multiple certificates.
+    pub ca_certificate: Option<PathBuf>,
+}
+
+impl AcmeConfig {
+    pub fn renew_before_expiry(&self) -> Duration {
+        Duration::from_secs(u64::from(self.renew_before_expiry_days.unwrap_or(30)) * 24 * 60 * 60)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ServerEncryptionKey {
     pub name: String,
     pub key_path: PathBuf,
@@ -205,7 +650,7 @@ pub struct SecureStorageConfig {
     /// Optional. If the manager instance is not available, this key
     /// will be used for opening the encryption.
     /// Should not be used in production.
-    pub encryption_key_text: Option<String>,
+    pub encryption_key_text: Option<SecretString>,
 
     /// Optional. Configure timeout for downloading the encryption key.
     pub key_download_timeout_seconds: Option<u32>,
@@ -227,6 +672,78 @@ pub struct SoftwareUpdateProviderConfig {
     /// "-old" when backend is updated. If there is already a directory
     /// with that name, it will be deleted.
     pub backend_data_reset_dir: Option<PathBuf>,
+    /// Optional. Enables supervised backend mode.
+    pub backend_supervisor: Option<BackendSupervisorConfig>,
+    /// Optional. Systemd unit which is used for controlling the backend.
+    pub backend_systemd_unit: Option<String>,
+    /// Optional. Default is selected using backend_supervisor and
+    /// backend_systemd_unit.
+    pub backend_controller: Option<BackendControllerType>,
+    /// Optional. Backend stop sequence config.
+    pub backend_stop: Option<BackendStopConfig>,
+    /// Optional. Restart the manager using the updated binary instead of
+    /// rebooting. Default is false.
+    pub manager_self_update: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendControllerType {
+    /// Use start-backend.sh and stop-backend.sh scripts.
+    Script,
+    /// Run backend as a child process of the manager.
+    Process,
+    /// Use systemd D-Bus API.
+    Systemd,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BackendSupervisorConfig {
+    pub args: Option<Vec<String>>,
+    /// Optional. Default is the backend binary directory.
+    pub working_dir: Option<PathBuf>,
+    /// Optional. Delay before the first restart. Default value is 1 second.
+    pub restart_min_delay_seconds: Option<u32>,
+    /// Optional. The restart delay is doubled after every restart until
+    /// this value is reached. Default value is 300 seconds.
+    pub restart_max_delay_seconds: Option<u32>,
+    /// Optional. Time to wait after SIGTERM before the backend is killed.
+    /// Default value is 10 seconds.
+    pub stop_timeout_seconds: Option<u32>,
+}
+
+impl BackendSupervisorConfig {
+    pub fn restart_min_delay(&self) -> Duration {
+        Duration::from_secs(self.restart_min_delay_seconds.unwrap_or(1).into())
+    }
+
+    pub fn restart_max_delay(&self) -> Duration {
+        Duration::from_secs(self.restart_max_delay_seconds.unwrap_or(300).into())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BackendStopConfig {
+    /// Optional. HTTP POST request is sent to this URL before the backend
+    /// is stopped. The backend should respond when it has finished
+    /// handling current requests.
+    pub drain_url: Option<Url>,
+    /// Optional. Default value is 30 seconds.
+    pub drain_timeout_seconds: Option<u32>,
+    /// Optional. Time to wait after SIGTERM before the backend is killed.
+    /// Overrides backend_supervisor.stop_timeout_seconds.
+    ///
+    /// Script controller passes the value to the stop script as the first
+    /// argument and the script must kill the backend after the timeout.
+    /// Without this value the manager waits until the stop script
+    /// completes.
+    pub stop_timeout_seconds: Option<u32>,
+}
+
+impl BackendStopConfig {
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_seconds.unwrap_or(30).into())
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -247,12 +764,287 @@ pub struct SoftwareBuilderConfig {
     pub backend_pre_build_script: Option<PathBuf>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct RebootIfNeededConfig {
-    /// Time when reboot should be done. Format "hh:mm". For example "12:00".
+    /// Optional. Time when reboot should be done. Format "hh:mm". For
+    /// example "12:00".
     ///
     /// This is an UTC time value without UTC offset.
-    pub time: TimeValue,
+    pub time: Option<TimeValue>,
+    /// Reboot is allowed anywhere inside a maintenance window.
+    #[serde(default)]
+    pub maintenance_windows: Vec<MaintenanceWindow>,
+    /// Optional. Take a reboot lease from a coordinator manager before
+    /// rebooting.
+    pub coordination: Option<RebootCoordinationConfig>,
+}
+
+impl RebootIfNeededConfig {
+    /// The time value is handled as a window without length, so reboot
+    /// is checked only at the start of it.
+    pub fn windows(&self) -> Vec<MaintenanceWindow> {
+        let time_window = self.time.clone().map(|time| MaintenanceWindow {
+            weekdays: None,
+            start: time.clone(),
+            end: time,
+            time_zone: None,
+            utc_offset: None,
+        });
+        self.maintenance_windows
+            .iter()
+            .cloned()
+            .chain(time_window)
+            .collect()
+    }
+
+    /// Returns the time if it is inside a maintenance window. Otherwise
+    /// returns start of the next window.
+    pub fn next_check_time(&self, time: OffsetDateTime) -> Option<OffsetDateTime> {
+        let windows = self.windows();
+        if windows.iter().any(|window| window.contains(time)) {
+            return Some(time);
+        }
+        windows
+            .iter()
+            .filter_map(|window| window.next_start_after(time))
+            .min()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RebootCoordinationConfig {
+    /// Reboot coordinator manager.
+    pub manager_base_url: Url,
+    /// Lease holder name. It must be unique between servers using the
+    /// same coordinator.
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RebootCoordinatorConfig {
+    /// Optional. Default value is 1.
+    pub max_concurrent_reboots: Option<u32>,
+    /// Optional. Lease is released automatically after this. Default
+    /// value is 1800 seconds.
+    pub lease_timeout_seconds: Option<u32>,
+}
+
+impl RebootCoordinatorConfig {
+    pub fn max_concurrent_reboots(&self) -> usize {
+        self.max_concurrent_reboots
+            .unwrap_or(1)
+            .try_into()
+            .unwrap_or(usize::MAX)
+    }
+
+    pub fn lease_timeout(&self) -> Duration {
+        Duration::from_secs(self.lease_timeout_seconds.unwrap_or(1800).into())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub pre_reboot: Vec<PreRebootHook>,
+    #[serde(default)]
+    pub post_boot: Vec<CustomCommand>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PreRebootHook {
+    #[serde(flatten)]
+    pub command: CustomCommand,
+    /// Optional. Cancel the reboot if the hook fails. Default value is
+    /// true.
+    pub abort_on_failure: Option<bool>,
+}
+
+impl PreRebootHook {
+    pub fn abort_on_failure(&self) -> bool {
+        self.abort_on_failure.unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MaintenanceWindow {
+    /// Optional. Default is every day. Weekday of the window start is
+    /// compared to these.
+    pub weekdays: Option<Vec<WeekdayValue>>,
+    pub start: TimeValue,
+    /// If end is before start, the window ends on the next day.
+    pub end: TimeValue,
+    /// Optional. IANA time zone name. The UTC offset is resolved
+    /// separately for every window, so daylight saving time is handled.
+    pub time_zone: Option<TimeZoneValue>,
+    /// Optional. Default is UTC. Fixed UTC offset, which can not be used
+    /// with the time zone.
+    pub utc_offset: Option<UtcOffsetValue>,
+}
+
+impl MaintenanceWindow {
+    /// Window without length never contains any time.
+    pub fn contains(&self, time: OffsetDateTime) -> bool {
+        let date = self.local_date(time);
+        // Window containing the time started today or yesterday.
+        [date.previous_day(), Some(date)]
+            .into_iter()
+            .flatten()
+            .filter_map(|date| self.start_on(date))
+            .any(|start| start <= time && time < start + self.length())
+    }
+
+    pub fn next_start_after(&self, time: OffsetDateTime) -> Option<OffsetDateTime> {
+        let date = self.local_date(time);
+        (0..=7)
+            .filter_map(|days| date.checked_add(time::Duration::days(days)))
+            .filter_map(|date| self.start_on(date))
+            .find(|start| *start > time)
+    }
+
+    /// Returns None if the window does not start on the date.
+    fn start_on(&self, date: Date) -> Option<OffsetDateTime> {
+        let weekday_allowed = self
+            .weekdays
+            .as_ref()
+            .map(|days| days.iter().any(|day| day.weekday() == date.weekday()))
+            .unwrap_or(true);
+        if !weekday_allowed {
+            return None;
+        }
+        let start = self.start.time()?;
+        Some(self.assume_local(date.with_time(start)))
+    }
+
+    pub fn length(&self) -> Duration {
+        let start = self.start.minutes_from_midnight();
+        let end = self.end.minutes_from_midnight();
+        let minutes = if end >= start {
+            end - start
+        } else {
+            24 * 60 - start + end
+        };
+        Duration::from_secs(u64::from(minutes) * 60)
+    }
+
+    fn local_date(&self, time: OffsetDateTime) -> Date {
+        match self.time_zone.as_ref() {
+            Some(zone) => time.to_timezone(zone.tz()).date(),
+            None => time.to_offset(self.offset()).date(),
+        }
+    }
+
+    /// If the local time is repeated because of a daylight saving time
+    /// change, the first occurrence is used. If the local time is skipped,
+    /// the offset after the change is used.
+    fn assume_local(&self, time: PrimitiveDateTime) -> OffsetDateTime {
+        let Some(zone) = self.time_zone.as_ref() else {
+            return time.assume_offset(self.offset());
+        };
+        match time.assume_timezone(zone.tz()) {
+            OffsetResult::Some(time) => time,
+            OffsetResult::Ambiguous(first, _) => first,
+            OffsetResult::None => time.assume_timezone_utc(zone.tz()),
+        }
+    }
+
+    fn offset(&self) -> UtcOffset {
+        self.utc_offset
+            .as_ref()
+            .map(|offset| offset.offset())
+            .unwrap_or(UtcOffset::UTC)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekdayValue {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl WeekdayValue {
+    pub fn weekday(&self) -> Weekday {
+        match self {
+            Self::Mon => Weekday::Monday,
+            Self::Tue => Weekday::Tuesday,
+            Self::Wed => Weekday::Wednesday,
+            Self::Thu => Weekday::Thursday,
+            Self::Fri => Weekday::Friday,
+            Self::Sat => Weekday::Saturday,
+            Self::Sun => Weekday::Sunday,
+        }
+    }
+}
+
+/// IANA time zone name. For example "Europe/Helsinki".
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeZoneValue {
+    tz: &'static Tz,
+}
+
+impl TimeZoneValue {
+    pub fn tz(&self) -> &'static Tz {
+        self.tz
+    }
+}
+
+impl TryFrom<String> for TimeZoneValue {
+    type Error = String;
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        time_tz::timezones::get_by_name(value.trim())
+            .map(|tz| TimeZoneValue { tz })
+            .ok_or_else(|| format!("Unknown time zone: {}", value))
+    }
+}
+
+impl From<TimeZoneValue> for String {
+    fn from(value: TimeZoneValue) -> Self {
+        time_tz::TimeZone::name(value.tz).to_string()
+    }
+}
+
+/// UTC offset. Format "+hh:mm" or "-hh:mm". For example "+02:00".
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(try_from = "String")]
+pub struct UtcOffsetValue {
+    /// Sign of the offset is included also in minutes.
+    pub hours: i8,
+    pub minutes: i8,
+}
+
+impl UtcOffsetValue {
+    pub fn offset(&self) -> UtcOffset {
+        UtcOffset::from_hms(self.hours, self.minutes, 0).unwrap_or(UtcOffset::UTC)
+    }
+}
+
+impl TryFrom<String> for UtcOffsetValue {
+    type Error = String;
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let value = value.trim();
+        let (sign, time) = if let Some(time) = value.strip_prefix('+') {
+            (1, time)
+        } else if let Some(time) = value.strip_prefix('-') {
+            (-1, time)
+        } else {
+            return Err(format!("UTC offset must start with + or -: {}", value));
+        };
+        let time = TimeValue::try_from(time.to_string())?;
+        if time.hours > 18 {
+            return Err(format!("UTC offset out of range: {}", value));
+        }
+        // Values are validated, so casting is safe.
+        Ok(UtcOffsetValue {
+            hours: sign * time.hours as i8,
+            minutes: sign * time.minutes as i8,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -275,6 +1067,9 @@ impl TryFrom<String> for TimeValue {
                 let minutes: u8 = minutes
                     .parse()
                     .map_err(|e: std::num::ParseIntError| e.to_string())?;
+                if hours > 23 || minutes > 59 {
+                    return Err(format!("Time value out of range: {}", value.trim()));
+                }
                 Ok(TimeValue { hours, minutes })
             }
             _ => Err(format!("Unknown values: {:?}", values)),
@@ -282,14 +1077,159 @@ impl TryFrom<String> for TimeValue {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl TimeValue {
+    pub fn time(&self) -> Option<Time> {
+        Time::from_hms(self.hours, self.minutes, 0).ok()
+    }
+
+    pub fn minutes_from_midnight(&self) -> u16 {
+        u16::from(self.hours) * 60 + u16::from(self.minutes)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SystemInfoConfig {
-    pub log_services: Vec<String>,
+    pub log_services: Vec<LogService>,
     pub remote_managers: Option<Vec<ManagerInstance>>,
+    /// Optional. Timeout for getting system info from one remote manager.
+    /// Default value is 10 seconds.
+    pub remote_timeout_seconds: Option<u32>,
+    /// Optional. Commands which output is included in system info.
+    pub commands: Option<Vec<CustomCommand>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl SystemInfoConfig {
+    pub fn remote_timeout(&self) -> Duration {
+        Duration::from_secs(self.remote_timeout_seconds.unwrap_or(10).into())
+    }
+}
+
+/// Service name or journalctl options for the service.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum LogService {
+    Name(String),
+    Options(LogServiceOptions),
+}
+
+impl LogService {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Name(name) => name,
+            Self::Options(options) => &options.name,
+        }
+    }
+
+    pub fn options(&self) -> Option<&LogServiceOptions> {
+        match self {
+            Self::Name(_) => None,
+            Self::Options(options) => Some(options),
+        }
+    }
+
+    pub fn lines(&self) -> u32 {
+        self.options()
+            .and_then(|options| options.lines)
+            .unwrap_or(20)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LogServiceOptions {
+    pub name: String,
+    /// Optional. Default value is 20.
+    pub lines: Option<u32>,
+    /// Optional. Value for journalctl --since.
+    pub since: Option<String>,
+    /// Optional. Value for journalctl --until.
+    pub until: Option<String>,
+    /// Optional. Value for journalctl --priority.
+    pub priority: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CustomCommand {
+    /// Name which is displayed in system info.
+    pub name: String,
+    pub command: String,
+    pub args: Option<Vec<String>>,
+    /// Optional. Default value is 30 seconds.
+    pub timeout_seconds: Option<u32>,
+    /// Optional. Run the command using sudo. Default value is false.
+    pub sudo: Option<bool>,
+}
+
+impl CustomCommand {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_seconds.unwrap_or(30).into())
+    }
+
+    pub fn sudo(&self) -> bool {
+        self.sudo.unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ManagerInstance {
     pub name: String,
     pub manager_base_url: Url,
 }
+
+#[cfg(test)]
+mod tests {
+    use time::Month;
+
+    use super::*;
+
+    fn window(start: &str, end: &str, time_zone: &str) -> MaintenanceWindow {
+        MaintenanceWindow {
+            weekdays: None,
+            start: TimeValue::try_from(start.to_string()).unwrap(),
+            end: TimeValue::try_from(end.to_string()).unwrap(),
+            time_zone: Some(TimeZoneValue::try_from(time_zone.to_string()).unwrap()),
+            utc_offset: None,
+        }
+    }
+
+    fn utc(month: Month, day: u8, hour: u8, minute: u8) -> OffsetDateTime {
+        Date::from_calendar_date(2024, month, day)
+            .unwrap()
+            .with_hms(hour, minute, 0)
+            .unwrap()
+            .assume_utc()
+    }
+
+    #[test]
+    fn time_zone_offset_follows_daylight_saving_time() {
+        let window = window("02:00", "05:00", "Europe/Helsinki");
+
+        // Winter time is UTC+2 and summer time is UTC+3.
+        assert_eq!(
+            window.next_start_after(utc(Month::January, 10, 12, 0)),
+            Some(utc(Month::January, 11, 0, 0))
+        );
+        assert_eq!(
+            window.next_start_after(utc(Month::July, 10, 12, 0)),
+            Some(utc(Month::July, 10, 23, 0))
+        );
+        assert!(window.contains(utc(Month::July, 10, 23, 30)));
+        assert!(!window.contains(utc(Month::July, 10, 2, 30)));
+        assert!(window.contains(utc(Month::January, 10, 2, 30)));
+    }
+
+    #[test]
+    fn skipped_local_time_uses_offset_after_change() {
+        // Clocks move from 03:00 to 04:00 on 2024-03-31 in Helsinki.
+        let window = window("03:30", "05:00", "Europe/Helsinki");
+
+        assert_eq!(
+            window.next_start_after(utc(Month::March, 30, 12, 0)),
+            Some(utc(Month::March, 31, 0, 30))
+        );
+    }
+
+    #[test]
+    fn unknown_time_zone_is_rejected() {
+        assert!(TimeZoneValue::try_from("Europe/Nowhere".to_string()).is_err());
+    }
+}
@@ -0,0 +1,314 @@
+//! Create new config file for init subcommand
+
+use std::{
+    fmt::Display,
+    fs::Permissions,
+    io::{Read, Write},
+    os::unix::fs::{OpenOptionsExt, PermissionsExt},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use error_stack::{Result, ResultExt};
+
+use super::{
+    args::{InitMode, ManagerRole},
+    file::{ConfigFile, DEFAULT_CONFIG_FILE_TEXT},
+    GetConfigError,
+};
+
+const DEFAULT_SCRIPTS_DIR: &str = "/app-server-tools/manager-tools";
+const DEFAULT_STORAGE_DIR: &str = "/app-secure-storage/app/app-manager-storage";
+const DEFAULT_PUBLIC_API: &str = "127.0.0.1:5000";
+const DEFAULT_REMOTE_MANAGER_URL: &str = "https://127.0.0.1:5000";
+const DEFAULT_ENCRYPTION_KEY_NAME: &str = "app-server";
+const DEFAULT_ENCRYPTION_KEY_PATH: &str = "data-key.key";
+const DEFAULT_AVAILABILITY_CHECK_PATH: &str = "/app-secure-storage/app";
+const DEFAULT_MANAGER_INSTALL_LOCATION: &str = "/home/app/binaries/app-manager";
+const DEFAULT_BACKEND_INSTALL_LOCATION: &str = "/app-secure-storage/app/binaries/app-backend";
+const DEFAULT_TLS_CERT: &str = "tls/server.crt";
+const DEFAULT_TLS_KEY: &str = "tls/server.key";
+const DEFAULT_ROOT_CERTIFICATE: &str = "tls/root.crt";
+
+/// Random API key length in bytes.
+const API_KEY_BYTES: usize = 32;
+
+pub fn handle_init_mode(mode: InitMode, config_file: &Path) -> Result<(), GetConfigError> {
+    if mode.print_example {
+        print!("{}", DEFAULT_CONFIG_FILE_TEXT);
+        return Ok(());
+    }
+
+    if config_file.exists() && !mode.force {
+        return Err(GetConfigError::InitFailed).attach_printable(format!(
+            "Config file {} already exists. Use --force to overwrite it.",
+            config_file.display()
+        ));
+    }
+
+    let config_text = ConfigGenerator { mode }.generate()?;
+
+    // Make sure that the generated config can be loaded.
+    let _: ConfigFile = toml::from_str(&config_text)
+        .change_context(GetConfigError::InitFailed)
+        .attach_printable("Generated config is invalid")?;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(config_file)
+        .change_context(GetConfigError::InitFailed)
+        .attach_printable(config_file.display().to_string())?;
+    // Mode is only used when the file is created, so fix permissions
+    // also when existing file is overwritten.
+    file.set_permissions(Permissions::from_mode(0o600))
+        .change_context(GetConfigError::InitFailed)?;
+    file.write_all(config_text.as_bytes())
+        .change_context(GetConfigError::InitFailed)?;
+
+    println!("Config file {} created", config_file.display());
+    println!("Run 'app-manager check-config' to check the config");
+
+    Ok(())
+}
+
+struct ConfigGenerator {
+    mode: InitMode,
+}
+
+impl ConfigGenerator {
+    fn generate(&self) -> Result<String, GetConfigError> {
+        let role = self.value(
+            "Role (key-provider, build-server, app-server)",
+            self.mode.role,
+            || ManagerRole::AppServer,
+        )?;
+        let api_key = match &self.mode.api_key {
+            Some(api_key) => api_key.clone(),
+            None => random_api_key()?,
+        };
+        let scripts_dir = self.path(
+            "Scripts directory",
+            &self.mode.scripts_dir,
+            DEFAULT_SCRIPTS_DIR,
+        )?;
+        let storage_dir = self.path(
+            "Storage directory",
+            &self.mode.storage_dir,
+            DEFAULT_STORAGE_DIR,
+        )?;
+        let public_api = self.value("Public API address", self.mode.public_api, || {
+            DEFAULT_PUBLIC_API.parse().expect("Default value is valid")
+        })?;
+
+        let mut text = String::new();
+        text.push_str(&format!("# Config for {} role\n\n", role));
+        text.push_str(&format!("api_key = {}\n", toml_str(&api_key)));
+        text.push_str(&format!("scripts_dir = {}\n", toml_path(&scripts_dir)));
+        text.push_str(&format!("storage_dir = {}\n", toml_path(&storage_dir)));
+        if self.mode.no_tls {
+            text.push_str("# TLS is disabled, so debug mode is required\n");
+            text.push_str("debug = true\n");
+        }
+        text.push_str("\n[socket]\n");
+        text.push_str(&format!("public_api = {}\n", toml_str(public_api)));
+
+        match role {
+            ManagerRole::KeyProvider => {
+                let name = self.string(
+                    "Encryption key name",
+                    &self.mode.encryption_key_name,
+                    DEFAULT_ENCRYPTION_KEY_NAME,
+                )?;
+                let key_path = self.path(
+                    "Encryption key file",
+                    &self.mode.encryption_key_path,
+                    DEFAULT_ENCRYPTION_KEY_PATH,
+                )?;
+                text.push_str("\n[[server_encryption_keys]]\n");
+                text.push_str(&format!("name = {}\n", toml_str(&name)));
+                text.push_str(&format!("key_path = {}\n", toml_path(&key_path)));
+            }
+            ManagerRole::BuildServer => {
+                let manager_git = self.required_string(
+                    "Manager git repository address",
+                    "--manager-git-address",
+                    &self.mode.manager_git_address,
+                )?;
+                let backend_git = self.required_string(
+                    "Backend git repository address",
+                    "--backend-git-address",
+                    &self.mode.backend_git_address,
+                )?;
+                text.push_str("\n[software_builder]\n");
+                text.push_str(&format!(
+                    "manager_download_git_address = {}\n",
+                    toml_str(&manager_git)
+                ));
+                text.push_str("manager_branch = \"main\"\n");
+                text.push_str("manager_binary = \"app-manager\"\n");
+                text.push_str(&format!(
+                    "backend_download_git_address = {}\n",
+                    toml_str(&backend_git)
+                ));
+                text.push_str("backend_branch = \"main\"\n");
+                text.push_str("backend_binary = \"app-backend\"\n");
+            }
+            ManagerRole::AppServer => {
+                let url = self.value(
+                    "Key provider and build server manager URL",
+                    self.mode.remote_manager_url.clone(),
+                    || {
+                        DEFAULT_REMOTE_MANAGER_URL
+                            .parse()
+                            .expect("Default value is valid")
+                    },
+                )?;
+                let name = self.string(
+                    "Encryption key name",
+                    &self.mode.encryption_key_name,
+                    DEFAULT_ENCRYPTION_KEY_NAME,
+                )?;
+                let url = url.as_str().trim_end_matches('/').to_string();
+                text.push_str("\n[secure_storage]\n");
+                text.push_str(&format!("manager_base_url = {}\n", toml_str(&url)));
+                text.push_str(&format!("encryption_key_name = {}\n", toml_str(&name)));
+                text.push_str(&format!(
+                    "availability_check_path = {}\n",
+                    toml_str(DEFAULT_AVAILABILITY_CHECK_PATH)
+                ));
+                text.push_str("\n[software_update_provider]\n");
+                text.push_str(&format!("manager_base_url = {}\n", toml_str(&url)));
+                text.push_str(&format!(
+                    "manager_install_location = {}\n",
+                    toml_str(DEFAULT_MANAGER_INSTALL_LOCATION)
+                ));
+                text.push_str(&format!(
+                    "backend_install_location = {}\n",
+                    toml_str(DEFAULT_BACKEND_INSTALL_LOCATION)
+                ));
+            }
+        }
+
+        if !self.mode.no_tls {
+            let cert = self.path("TLS certificate", &self.mode.tls_cert, DEFAULT_TLS_CERT)?;
+            let key = self.path("TLS private key", &self.mode.tls_key, DEFAULT_TLS_KEY)?;
+            let root = self.path(
+                "TLS root certificate",
+                &self.mode.root_certificate,
+                DEFAULT_ROOT_CERTIFICATE,
+            )?;
+            text.push_str("\n[tls]\n");
+            text.push_str(&format!("public_api_cert = {}\n", toml_path(&cert)));
+            text.push_str(&format!("public_api_key = {}\n", toml_path(&key)));
+            text.push_str(&format!("root_certificate = {}\n", toml_path(&root)));
+        }
+
+        Ok(text)
+    }
+
+    fn path(
+        &self,
+        question: &str,
+        value: &Option<PathBuf>,
+        default: &str,
+    ) -> Result<PathBuf, GetConfigError> {
+        let value = value.as_ref().map(|path| path.display().to_string());
+        self.value(question, value, || default.to_string())
+            .map(PathBuf::from)
+    }
+
+    fn string(
+        &self,
+        question: &str,
+        value: &Option<String>,
+        default: &str,
+    ) -> Result<String, GetConfigError> {
+        self.value(question, value.clone(), || default.to_string())
+    }
+
+    fn required_string(
+        &self,
+        question: &str,
+        arg_name: &str,
+        value: &Option<String>,
+    ) -> Result<String, GetConfigError> {
+        if let Some(value) = value {
+            return Ok(value.clone());
+        }
+        if !self.mode.interactive {
+            return Err(GetConfigError::InitFailed)
+                .attach_printable(format!("Argument {} is required", arg_name));
+        }
+        loop {
+            let answer = prompt(question, None)?;
+            if !answer.is_empty() {
+                return Ok(answer);
+            }
+        }
+    }
+
+    /// Returns argument value if it exists. Otherwise the value is asked
+    /// in interactive mode or the default value is used.
+    fn value<T: FromStr + Display>(
+        &self,
+        question: &str,
+        value: Option<T>,
+        default: impl FnOnce() -> T,
+    ) -> Result<T, GetConfigError>
+    where
+        T::Err: Display,
+    {
+        if let Some(value) = value {
+            return Ok(value);
+        }
+        let default = default();
+        if !self.mode.interactive {
+            return Ok(default);
+        }
+        loop {
+            let answer = prompt(question, Some(&default.to_string()))?;
+            if answer.is_empty() {
+                return Ok(default);
+            }
+            match answer.parse() {
+                Ok(value) => return Ok(value),
+                Err(e) => println!("Invalid value: {}", e),
+            }
+        }
+    }
+}
+
+fn prompt(question: &str, default: Option<&str>) -> Result<String, GetConfigError> {
+    match default {
+        Some(default) => print!("{} [{}]: ", question, default),
+        None => print!("{}: ", question),
+    }
+    std::io::stdout()
+        .flush()
+        .change_context(GetConfigError::InitFailed)?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .change_context(GetConfigError::InitFailed)?;
+    Ok(answer.trim().to_string())
+}
+
+fn random_api_key() -> Result<String, GetConfigError> {
+    let mut bytes = [0u8; API_KEY_BYTES];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut random| random.read_exact(&mut bytes))
+        .change_context(GetConfigError::InitFailed)
+        .attach_printable("Reading random bytes failed")?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn toml_str(value: impl ToString) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+fn toml_path(path: &Path) -> String {
+    toml_str(path.display())
+}
@@ -19,22 +19,69 @@ pub mod utils;
 fn main() {
     let args = crate::config::args::get_config();
 
-    if let Some(AppMode::Api(api_client_mode)) = args.app_mode {
-        let runtime = tokio::runtime::Runtime::new().unwrap();
+    match args.app_mode.clone() {
+        Some(AppMode::Api(api_client_mode)) => {
+            let config_file = args.config_file().unwrap();
+            let runtime = tokio::runtime::Runtime::new().unwrap();
 
-        runtime.block_on(async {
-            let result = self::client::handle_api_client_mode(api_client_mode).await;
-            match result {
-                Ok(_) => std::process::exit(0),
+            runtime.block_on(async {
+                let result =
+                    self::client::handle_api_client_mode(api_client_mode, &config_file).await;
+                match result {
+                    Ok(_) => std::process::exit(0),
+                    Err(e) => {
+                        eprintln!("{:?}", e);
+                        std::process::exit(1)
+                    }
+                }
+            })
+        }
+        Some(AppMode::Init(init_mode)) => {
+            let config_file = args.config_file().unwrap();
+            match crate::config::init::handle_init_mode(init_mode, &config_file) {
+                Ok(()) => std::process::exit(0),
+                Err(e) => {
+                    eprintln!("{:?}", e);
+                    std::process::exit(1)
+                }
+            }
+        }
+        Some(AppMode::UnlockApi) => {
+            let config_file = args.config_file().unwrap();
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            match runtime.block_on(crate::server::state::handle_unlock_api_mode(&config_file)) {
+                Ok(()) => std::process::exit(0),
                 Err(e) => {
                     eprintln!("{:?}", e);
                     std::process::exit(1)
                 }
             }
-        })
-    } else {
-        let config = crate::config::get_config(args).unwrap();
-        let runtime = tokio::runtime::Runtime::new().unwrap();
-        runtime.block_on(async { self::server::AppServer::new(config).run().await })
+        }
+        Some(AppMode::CheckConfig) => {
+            let config_file = args.config_file().unwrap();
+            match crate::config::check::handle_check_config_mode(&config_file) {
+                Ok(true) => std::process::exit(0),
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("{:?}", e);
+                    std::process::exit(1)
+                }
+            }
+        }
+        None => {
+            let handoff = self::server::self_update::HandoffState::take_from_env();
+            let config = crate::config::get_config(args).unwrap();
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let manager_handoff = runtime.block_on(async {
+                self::server::AppServer::new(config, handoff, None)
+                    .run()
+                    .await
+            });
+            if let Some(manager_handoff) = manager_handoff {
+                let e = manager_handoff.exec();
+                eprintln!("Starting updated manager failed. Error: {}", e);
+                std::process::exit(1)
+            }
+        }
     }
 }
@@ -17,37 +17,54 @@ use tokio::{
         self,
         unix::{Signal, SignalKind},
     },
-    sync::{broadcast, mpsc},
+    sync::{broadcast, mpsc, watch},
     task::JoinHandle,
 };
 use tokio_rustls::{rustls::ServerConfig, TlsAcceptor};
 use tower::Service;
 use tower_http::trace::TraceLayer;
 use tracing::{error, info, log::warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 use crate::{
     api::ApiDoc,
     config::{
+        file::BackendControllerType,
         info::{BUILD_INFO_CARGO_PKG_VERSION, BUILD_INFO_GIT_DESCRIBE},
-        Config,
+        load_public_api_tls_config, Config,
     },
     server::{
-        app::App, backend_controller::BackendController, build::BuildManager, client::ApiClient,
-        mount::MountManager, state::StateStorage,
+        acme::{AcmeChallenges, AcmeManager},
+        app::App,
+        backend_controller::{BackendController, ServiceController},
+        backend_supervisor::BackendSupervisor,
+        build::BuildManager,
+        client::ApiClient,
+        mount::MountManager,
+        reload::ConfigReloader,
+        self_update::{self_update_channel, HandoffState, ManagerHandoff},
+        state::StateStorage,
     },
 };
 
+pub mod acme;
 pub mod app;
 pub mod backend_controller;
+pub mod backend_supervisor;
 pub mod build;
 pub mod client;
+pub mod hooks;
 pub mod info;
+pub mod logging;
+pub mod metrics;
 pub mod mount;
 pub mod reboot;
+pub mod reboot_coordinator;
+pub mod reload;
+pub mod self_update;
 pub mod state;
+pub mod systemd;
 pub mod update;
 
 /// Drop this when quit starts
@@ -58,32 +75,30 @@ pub type ServerQuitWatcher = broadcast::Receiver<()>;
 
 pub struct AppServer {
     config: Arc<Config>,
+    /// Available if the previous manager process handed off to this
+    /// process after manager update.
+    handoff: Option<HandoffState>,
+    /// Overrides the backend controller selected using the config.
+    backend_controller: Option<Arc<dyn ServiceController>>,
 }
 
 impl AppServer {
-    pub fn new(config: Config) -> Self {
+    pub fn new(
+        config: Config,
+        handoff: Option<HandoffState>,
+        backend_controller: Option<Arc<dyn ServiceController>>,
+    ) -> Self {
         Self {
             config: config.into(),
+            handoff,
+            backend_controller,
         }
     }
 
-    pub async fn run(self) {
-        let log_with_timestamp_layer = if self.config.log_timestamp() {
-            Some(tracing_subscriber::fmt::layer().with_filter(EnvFilter::from_default_env()))
-        } else {
-            None
-        };
-
-        let log_without_timestamp_layer = if self.config.log_timestamp() {
-            None
-        } else {
-            Some(tracing_subscriber::fmt::layer().without_time().with_filter(EnvFilter::from_default_env()))
-        };
-
-        tracing_subscriber::registry()
-            .with(log_with_timestamp_layer)
-            .with(log_without_timestamp_layer)
-            .init();
+    /// Returns Some if the manager should be restarted using the
+    /// updated manager binary.
+    pub async fn run(self) -> Option<ManagerHandoff> {
+        let _logging_guard = logging::init_logging(&self.config);
 
         info!(
             "app-manager version: {}-{}",
@@ -94,18 +109,35 @@ impl AppServer {
             warn!("Debug mode is enabled");
         }
 
+        if self.handoff.is_some() {
+            info!("Manager was restarted after manager update");
+        }
+
+        for warning in self.config.secret_permission_warnings() {
+            warn!("{}", warning);
+        }
+
         let (server_quit_handle, server_quit_watcher) = broadcast::channel(1);
         let mut terminate_signal = signal::unix::signal(SignalKind::terminate()).unwrap();
+        let mut reload_signal = signal::unix::signal(SignalKind::hangup()).unwrap();
+
+        // Load state. The state file is not available before the
+        // secure storage is mounted, so it is loaded again later.
+
+        let state: Arc<StateStorage> = StateStorage::new(&self.config).into();
+        state.load().await;
 
         // Start build manager
 
-        let (build_manager_quit_handle, build_manager_handle) =
-            BuildManager::new_manager(self.config.clone(), server_quit_watcher.resubscribe());
+        let (build_manager_quit_handle, build_manager_handle) = BuildManager::new_manager(
+            self.config.clone(),
+            state.clone(),
+            server_quit_watcher.resubscribe(),
+        );
 
         // Create API client
 
         let api_client: Arc<ApiClient> = ApiClient::new(&self.config).unwrap().into();
-        let state: Arc<StateStorage> = StateStorage::new().into();
 
         // Start reboot manager
 
@@ -116,38 +148,103 @@ impl AppServer {
             server_quit_watcher.resubscribe(),
         );
 
+        let config_reloader: Arc<ConfigReloader> = ConfigReloader::new(
+            self.config.clone(),
+            api_client.clone(),
+            reboot_manager_handle.clone(),
+        )
+        .into();
+
+        // Start backend supervisor
+
+        let (backend_supervisor_quit_handle, backend_supervisor_handle) =
+            BackendSupervisor::new_manager(self.config.clone(), server_quit_watcher.resubscribe());
+        let backend_controller = match self.backend_controller.clone() {
+            Some(controller) => BackendController::new(controller, &self.config),
+            None => BackendController::from_config(&self.config, backend_supervisor_handle),
+        };
+        let (self_update_handle, mut self_update_receiver) = self_update_channel();
+
         // Start update manager
 
         let (update_manager_quit_handle, update_manager_handle) = update::UpdateManager::new_manager(
             self.config.clone(),
             server_quit_watcher.resubscribe(),
             api_client.clone(),
-            reboot_manager_handle,
+            reboot_manager_handle.clone(),
+            backend_controller.clone(),
+            self_update_handle,
+            state.clone(),
         );
 
         // Create app
 
+        let acme_challenges: Arc<AcmeChallenges> = AcmeChallenges::default().into();
+
         let mut app = App::new(
             self.config.clone(),
             api_client,
             build_manager_handle.into(),
             update_manager_handle.into(),
+            acme_challenges.clone(),
+            config_reloader.clone(),
+            backend_controller.clone(),
+            reboot_manager_handle.clone(),
+            state.clone(),
         )
         .await;
 
         // Start API server
 
+        let tls_config_sender = self
+            .config
+            .public_api_tls_config()
+            .map(|tls_config| watch::channel(tls_config.clone()).0);
+
         let (server_task1, server_task2) = self
-            .create_public_api_server_task(&mut app, server_quit_watcher.resubscribe())
+            .create_public_api_server_task(
+                &mut app,
+                tls_config_sender.as_ref(),
+                server_quit_watcher.resubscribe(),
+            )
             .await;
 
+        let acme_challenge_server_task = match self
+            .config
+            .tls_config()
+            .and_then(|tls| tls.acme.as_ref())
+            .and_then(|acme| acme.http_challenge_port)
+        {
+            Some(port) => {
+                let addr = SocketAddr::new(self.config.socket().public_api.ip(), port);
+                info!("ACME challenge responses are available on {}", addr);
+                let handle = self
+                    .create_server_task_no_tls(
+                        app.create_acme_challenge_router(),
+                        addr,
+                        "ACME challenge",
+                        server_quit_watcher.resubscribe(),
+                    )
+                    .await;
+                Some(handle)
+            }
+            None => None,
+        };
+
         // Mount encrypted storage if needed
 
         let mount_manager = MountManager::new(self.config.clone(), app.state(), state.clone());
 
         if let Some(encryption_key_provider) = self.config.secure_storage_config() {
             loop {
-                match mount_manager.mount_if_needed(encryption_key_provider).await {
+                let previous_mode = match self.handoff.as_ref().and_then(|h| h.mount_mode()) {
+                    Some(mode) => Some(mode),
+                    None => state.get(|s| s.persistent.mount_mode()).await,
+                };
+                match mount_manager
+                    .mount_if_needed(encryption_key_provider, previous_mode)
+                    .await
+                {
                     Ok(()) => {
                         break;
                     }
@@ -160,7 +257,7 @@ impl AppServer {
 
                 tokio::select! {
                     _ = Self::wait_quit_signal(&mut terminate_signal) => {
-                        return;
+                        return None;
                     }
                     _ = tokio::time::sleep(Duration::from_secs(60*60)) => {} // check again in an hour
                 }
@@ -181,31 +278,102 @@ impl AppServer {
             }
         }
 
+        state.load().await;
+
+        // Run post-boot hooks. Manager restarts are detected using
+        // the boot ID.
+
+        match hooks::current_boot_id().await {
+            Ok(boot_id) => hooks::run_post_boot_hooks_once(&self.config, &state, boot_id).await,
+            Err(e) => warn!("Post-boot hooks are not run. Error: {:?}", e),
+        }
+
+        // Start ACME manager
+
+        let acme_manager_quit_handle = match (
+            tls_config_sender.as_ref(),
+            self.config.tls_config().and_then(|tls| tls.acme.as_ref()),
+        ) {
+            (Some(tls_config_sender), Some(_)) => Some(AcmeManager::new_manager(
+                self.config.clone(),
+                acme_challenges,
+                tls_config_sender.clone(),
+                server_quit_watcher.resubscribe(),
+            )),
+            _ => None,
+        };
+
         // Start backend if it is installed
 
         if let Some(update_config) = self.config.software_update_provider() {
-            if update_config.backend_install_location.exists() {
-                info!("Starting backend");
-                match BackendController::new(&self.config).start_backend().await {
-                    Ok(()) => {
-                        info!("Backend started");
-                    }
-                    Err(e) => {
-                        warn!("Backend start failed. Error: {:?}", e);
-                    }
+            if self.handoff.as_ref().is_some_and(|h| h.backend_running) {
+                info!("Backend is already running");
+            } else if update_config.backend_install_location.exists() {
+                if let Err(e) = backend_controller.start_backend(None).await {
+                    warn!("Backend start failed. Error: {:?}", e);
                 }
             } else {
                 warn!("Backend starting failed. Backend is not installed");
             }
         }
 
-        // Wait until quit signal
-        Self::wait_quit_signal(&mut terminate_signal).await;
+        // Start reboot checks and allow the next server to reboot
+
+        if let Err(e) = reboot_manager_handle.startup_complete().await {
+            warn!("Reboot manager startup notification failed. Error: {:?}", e);
+        }
+
+        // Wait until quit signal. Config is reloaded when SIGHUP is received.
+        let mut self_update = false;
+        loop {
+            tokio::select! {
+                _ = Self::wait_quit_signal(&mut terminate_signal) => break,
+                _ = self_update_receiver.wait_restart_request() => {
+                    self_update = true;
+                    break;
+                }
+                _ = reload_signal.recv() => {
+                    info!("Reloading config");
+                    if let Err(e) = config_reloader.reload().await {
+                        error!("Config reloading failed. Error: {:?}", e);
+                    }
+                }
+            }
+        }
 
         // Quit started
 
         info!("Manager quit started");
 
+        // Backend keeps running during manager self-update if it is not
+        // a child process of the manager.
+        let keep_backend_running =
+            self_update && self.config.backend_controller_type() != BackendControllerType::Process;
+
+        // Stop backend before other tasks quit, so that the backend
+        // supervisor is still available.
+        if self.config.software_update_provider().is_some() && !keep_backend_running {
+            let stop = backend_controller.stop_backend(None);
+            let result = match backend_controller.shutdown_timeout() {
+                Some(timeout) => tokio::time::timeout(timeout, stop)
+                    .await
+                    .map_err(|_| timeout),
+                None => Ok(stop.await),
+            };
+            match result {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => {
+                    warn!("Backend stopping failed. Error: {:?}", e);
+                }
+                Err(timeout) => {
+                    warn!(
+                        "Backend stopping did not complete in {} seconds",
+                        timeout.as_secs()
+                    );
+                }
+            }
+        }
+
         drop(server_quit_handle);
 
         // Wait until all tasks quit
@@ -219,24 +387,37 @@ impl AppServer {
                 .expect("Second Manager API server task panic detected");
         }
 
+        if let Some(task) = acme_challenge_server_task {
+            task.await
+                .expect("ACME challenge server task panic detected");
+        }
+
+        if let Some(handle) = acme_manager_quit_handle {
+            handle.wait_quit().await;
+        }
+
         build_manager_quit_handle.wait_quit().await;
         reboot_manager_quit_handle.wait_quit().await;
         update_manager_quit_handle.wait_quit().await;
 
-        if self.config.software_update_provider().is_some() {
-            info!("Stopping backend");
-            match BackendController::new(&self.config).stop_backend().await {
-                Ok(()) => {
-                    info!("Backend stopped");
-                }
-                Err(e) => {
-                    warn!("Backend stopping failed. Error: {:?}", e);
-                }
-            }
-        }
+        // Supervised backend is stopped when the supervisor quits if
+        // the stop sequence did not stop it.
+        backend_supervisor_quit_handle.wait_quit().await;
 
         drop(app);
 
+        if self_update {
+            let mount_mode = state.get(|s| s.mount_state.mode()).await;
+            info!("Manager quit done. Starting the updated manager.");
+            return self
+                .config
+                .software_update_provider()
+                .map(|update| ManagerHandoff {
+                    binary: update.manager_install_location.clone(),
+                    state: HandoffState::new(mount_mode, keep_backend_running),
+                });
+        }
+
         if let Some(config) = self.config.secure_storage_config() {
             match mount_manager.unmount_if_needed(config).await {
                 Ok(()) => {
@@ -249,6 +430,8 @@ impl AppServer {
         }
 
         info!("Manager quit done");
+
+        None
     }
 
     pub async fn wait_quit_signal(terminate_signal: &mut Signal) {
@@ -267,6 +450,7 @@ impl AppServer {
     pub async fn create_public_api_server_task(
         &self,
         app: &mut App,
+        tls_config_sender: Option<&watch::Sender<Arc<ServerConfig>>>,
         quit_notification: ServerQuitWatcher,
     ) -> (JoinHandle<()>, Option<JoinHandle<()>>) {
         let router = {
@@ -286,17 +470,17 @@ impl AppServer {
         let addr = self.config.socket().public_api;
         info!("Public API is available on {}", addr);
 
-        let join_handle = if let Some(tls_config) = self.config.public_api_tls_config() {
+        let join_handle = if let Some(tls_config_sender) = tls_config_sender {
             self.create_server_task_with_tls(
                 addr,
-                router.clone(),
-                tls_config.clone(),
+                router.clone().merge(app.create_metrics_router()),
+                tls_config_sender.subscribe(),
                 quit_notification.resubscribe(),
             )
             .await
         } else {
             self.create_server_task_no_tls(
-                router.clone(),
+                router.clone().merge(app.create_metrics_router()),
                 addr,
                 "Public API",
                 quit_notification.resubscribe(),
@@ -308,6 +492,8 @@ impl AppServer {
             if let Some(port) = self.config.socket().second_public_api_localhost_only_port {
                 let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port);
                 info!("Public API is available also on {}", addr);
+                info!("Metrics are available without API key on {}", addr);
+                let router = router.merge(app.create_localhost_metrics_router());
                 let handle = self
                     .create_server_task_no_tls(router, addr, "Second public API", quit_notification)
                     .await;
@@ -323,14 +509,17 @@ impl AppServer {
         &self,
         addr: SocketAddr,
         router: Router,
-        tls_config: Arc<ServerConfig>,
+        mut tls_config: watch::Receiver<Arc<ServerConfig>>,
         mut quit_notification: ServerQuitWatcher,
     ) -> JoinHandle<()> {
         let mut listener = TcpListener::bind(addr)
             .await
             .expect("Address not available");
-        let acceptor = TlsAcceptor::from(tls_config);
+        let mut acceptor = TlsAcceptor::from(tls_config.borrow_and_update().clone());
         let app_service = router.into_make_service_with_connect_info::<SocketAddr>();
+        let tls_files = self.config.tls_config().cloned();
+        let storage_dir = self.config.storage_dir().to_path_buf();
+        let mut reload_signal = signal::unix::signal(SignalKind::hangup()).unwrap();
 
         tokio::spawn(async move {
             let (drop_after_connection, mut wait_all_connections) = mpsc::channel::<()>(1);
@@ -342,6 +531,35 @@ impl AppServer {
                     _ = quit_notification.recv() => {
                         break;
                     }
+                    _ = reload_signal.recv() => {
+                        // Existing connections keep using the previous
+                        // TLS config.
+                        if let Some(tls_files) = &tls_files {
+                            match load_public_api_tls_config(tls_files, &storage_dir) {
+                                Ok(tls_config) => {
+                                    acceptor = TlsAcceptor::from(tls_config);
+                                    info!("Public API TLS certificate reloaded");
+                                }
+                                Err(e) => {
+                                    error!("Public API TLS certificate reloading failed. Error: {:?}", e);
+                                }
+                            }
+                        }
+                        continue;
+                    }
+                    result = tls_config.changed() => {
+                        match result {
+                            Ok(()) => {
+                                acceptor = TlsAcceptor::from(tls_config.borrow_and_update().clone());
+                                info!("Public API TLS config updated");
+                            }
+                            Err(_) => {
+                                warn!("Public API TLS config channel closed");
+                                break;
+                            }
+                        }
+                        continue;
+                    }
                     addr = next_addr_stream => {
                         match addr {
                             Ok(stream_and_addr) => {
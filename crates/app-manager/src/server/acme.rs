@@ -0,0 +1,839 @@
+//! Get public API certificate from ACME server
+
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fs::Permissions,
+    future::Future,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+
+use bytes::Bytes;
+use error_stack::{Result, ResultExt};
+use http_body_util::{BodyExt, Full};
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, BytesResponse, ChallengeType, HttpClient,
+    Identifier, NewAccount, NewOrder, OrderStatus,
+};
+use time::OffsetDateTime;
+use tokio::{
+    io::AsyncWriteExt,
+    sync::{watch, RwLock},
+    task::JoinHandle,
+    time::sleep,
+};
+use tokio_rustls::rustls::ServerConfig;
+use tracing::{info, warn};
+
+use super::ServerQuitWatcher;
+use crate::{
+    config::{
+        file::{AcmeConfig, TlsConfig, ACME_ACCOUNT_FILE_NAME, ACME_DIR_NAME},
+        load_public_api_tls_config, load_root_certificates, Config,
+    },
+    utils::ContextExt,
+};
+
+const CERTIFICATE_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+const RETRY_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const ORDER_POLL_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_secs(5)
+};
+const ORDER_POLL_MAX_COUNT: usize = 60;
+
+#[derive(thiserror::Error, Debug)]
+pub enum AcmeError {
+    #[error("ACME config is missing")]
+    AcmeConfigMissing,
+
+    #[error("ACME account error")]
+    Account,
+
+    #[error("ACME order error")]
+    Order,
+
+    #[error("HTTP-01 challenge is not available")]
+    Http01ChallengeMissing,
+
+    #[error("Authorization failed")]
+    AuthorizationFailed,
+
+    #[error("Order timeout")]
+    OrderTimeout,
+
+    #[error("Certificate signing request creation failed")]
+    CreateCsr,
+
+    #[error("File reading failed")]
+    FileReadingFailed,
+
+    #[error("File writing failed")]
+    FileWritingFailed,
+
+    #[error("Invalid input")]
+    InvalidInput,
+
+    #[error("TLS config loading failed")]
+    LoadTlsConfig,
+
+    #[error("HTTP client creation failed")]
+    HttpClient,
+}
+
+/// HTTP-01 challenge key authorizations which are served from
+/// "/.well-known/acme-challenge/{token}".
+#[derive(Debug, Default)]
+pub struct AcmeChallenges {
+    key_authorizations: RwLock<HashMap<String, String>>,
+}
+
+impl AcmeChallenges {
+    pub async fn key_authorization(&self, token: &str) -> Option<String> {
+        self.key_authorizations.read().await.get(token).cloned()
+    }
+
+    async fn insert(&self, token: String, key_authorization: String) {
+        self.key_authorizations
+            .write()
+            .await
+            .insert(token, key_authorization);
+    }
+
+    async fn clear(&self) {
+        self.key_authorizations.write().await.clear();
+    }
+}
+
+#[derive(Debug)]
+pub struct AcmeManagerQuitHandle {
+    task: JoinHandle<()>,
+}
+
+impl AcmeManagerQuitHandle {
+    pub async fn wait_quit(self) {
+        match self.task.await {
+            Ok(()) => (),
+            Err(e) => {
+                warn!("ACME manager quit failed. Error: {:?}", e);
+            }
+        }
+    }
+}
+
+pub struct AcmeManager {
+    config: Arc<Config>,
+    challenges: Arc<AcmeChallenges>,
+    tls_config_sender: watch::Sender<Arc<ServerConfig>>,
+}
+
+impl AcmeManager {
+    pub fn new_manager(
+        config: Arc<Config>,
+        challenges: Arc<AcmeChallenges>,
+        tls_config_sender: watch::Sender<Arc<ServerConfig>>,
+        quit_notification: ServerQuitWatcher,
+    ) -> AcmeManagerQuitHandle {
+        let manager = Self {
+            config,
+            challenges,
+            tls_config_sender,
+        };
+
+        let task = tokio::spawn(manager.run(quit_notification));
+
+        AcmeManagerQuitHandle { task }
+    }
+
+    pub async fn run(self, mut quit_notification: ServerQuitWatcher) {
+        loop {
+            let next_check = match self.renew_certificate_if_needed().await {
+                Ok(()) => CERTIFICATE_CHECK_INTERVAL,
+                Err(e) => {
+                    warn!("ACME certificate renewal failed. Error: {:?}", e);
+                    RETRY_INTERVAL
+                }
+            };
+
+            tokio::select! {
+                _ = sleep(next_check) => (),
+                _ = quit_notification.recv() => {
+                    return;
+                }
+            }
+        }
+    }
+
+    pub async fn renew_certificate_if_needed(&self) -> Result<(), AcmeError> {
+        let (tls_config, acme_config) = self.acme_config()?;
+        let (cert_path, key_path) = tls_config
+            .public_api_cert_and_key(self.config.storage_dir())
+            .ok_or(AcmeError::AcmeConfigMissing.report())?;
+
+        if !Self::renewal_needed(&cert_path, acme_config) {
+            info!("ACME certificate is up to date");
+            // Certificate might be from previous manager run, so
+            // make sure that it is in use.
+            return self.update_tls_config(tls_config);
+        }
+
+        info!("Requesting new certificate from ACME server");
+        let (cert_chain, private_key) = self.request_certificate(acme_config).await?;
+
+        write_private_files(&[
+            (&key_path, private_key.as_bytes()),
+            (&cert_path, cert_chain.as_bytes()),
+        ])
+        .await?;
+        info!("New ACME certificate saved");
+
+        self.update_tls_config(tls_config)
+    }
+
+    /// Renewal is needed if the certificate expires before the renewal
+    /// margin or if the certificate is not readable.
+    fn renewal_needed(cert_path: &Path, acme_config: &AcmeConfig) -> bool {
+        if !cert_path.exists() {
+            return true;
+        }
+
+        match certificate_expiry_time(cert_path) {
+            Ok(not_after) => {
+                OffsetDateTime::now_utc() + acme_config.renew_before_expiry() >= not_after
+            }
+            Err(e) => {
+                warn!(
+                    "Reading ACME certificate expiry time failed. Error: {:?}",
+                    e
+                );
+                true
+            }
+        }
+    }
+
+    fn update_tls_config(&self, tls_config: &TlsConfig) -> Result<(), AcmeError> {
+        let server_config = load_public_api_tls_config(tls_config, self.config.storage_dir())
+            .change_context(AcmeError::LoadTlsConfig)?;
+        self.tls_config_sender.send_replace(server_config);
+        Ok(())
+    }
+
+    /// Returns PEM encoded certificate chain and private key.
+    async fn request_certificate(
+        &self,
+        acme_config: &AcmeConfig,
+    ) -> Result<(String, String), AcmeError> {
+        let account = self.load_or_create_account(acme_config).await?;
+
+        let identifiers: Vec<Identifier> = acme_config
+            .domains
+            .iter()
+            .map(|domain| Identifier::Dns(domain.clone()))
+            .collect();
+        let mut order = account
+            .new_order(&NewOrder {
+                identifiers: &identifiers,
+            })
+            .await
+            .change_context(AcmeError::Order)?;
+
+        let authorizations = order
+            .authorizations()
+            .await
+            .change_context(AcmeError::Order)?;
+        let mut challenge_urls = vec![];
+        for authorization in &authorizations {
+            match authorization.status {
+                AuthorizationStatus::Pending => (),
+                AuthorizationStatus::Valid => continue,
+                status => {
+                    return Err(AcmeError::AuthorizationFailed.report())
+                        .attach_printable(format!("{:?}", status))
+                }
+            }
+
+            let challenge = authorization
+                .challenges
+                .iter()
+                .find(|c| c.r#type == ChallengeType::Http01)
+                .ok_or(AcmeError::Http01ChallengeMissing.report())?;
+
+            let key_authorization = order.key_authorization(challenge);
+            self.challenges
+                .insert(
+                    challenge.token.clone(),
+                    key_authorization.as_str().to_string(),
+                )
+                .await;
+            challenge_urls.push(challenge.url.clone());
+        }
+
+        for url in &challenge_urls {
+            order
+                .set_challenge_ready(url)
+                .await
+                .change_context(AcmeError::Order)?;
+        }
+
+        let result = Self::wait_order_ready_and_finalize(&mut order, acme_config).await;
+        self.challenges.clear().await;
+        result
+    }
+
+    async fn wait_order_ready_and_finalize(
+        order: &mut instant_acme::Order,
+        acme_config: &AcmeConfig,
+    ) -> Result<(String, String), AcmeError> {
+        let mut poll_count = 0;
+        loop {
+            sleep(ORDER_POLL_INTERVAL).await;
+            let state = order.refresh().await.change_context(AcmeError::Order)?;
+            match state.status {
+                OrderStatus::Ready => break,
+                OrderStatus::Invalid => {
+                    return Err(AcmeError::AuthorizationFailed.report())
+                        .attach_printable("Order is invalid")
+                }
+                _ => (),
+            }
+            poll_count += 1;
+            if poll_count >= ORDER_POLL_MAX_COUNT {
+                return Err(AcmeError::OrderTimeout.report());
+            }
+        }
+
+        let mut params = rcgen::CertificateParams::new(acme_config.domains.clone())
+            .change_context(AcmeError::CreateCsr)?;
+        params.distinguished_name = rcgen::DistinguishedName::new();
+        let private_key = rcgen::KeyPair::generate().change_context(AcmeError::CreateCsr)?;
+        let csr = params
+            .serialize_request(&private_key)
+            .change_context(AcmeError::CreateCsr)?;
+
+        order
+            .finalize(csr.der())
+            .await
+            .change_context(AcmeError::Order)?;
+
+        let mut poll_count = 0;
+        let cert_chain = loop {
+            if let Some(cert_chain) = order.certificate().await.change_context(AcmeError::Order)? {
+                break cert_chain;
+            }
+            poll_count += 1;
+            if poll_count >= ORDER_POLL_MAX_COUNT {
+                return Err(AcmeError::OrderTimeout.report());
+            }
+            sleep(ORDER_POLL_INTERVAL).await;
+        };
+
+        Ok((cert_chain, private_key.serialize_pem()))
+    }
+
+    async fn load_or_create_account(&self, acme_config: &AcmeConfig) -> Result<Account, AcmeError> {
+        let account_path = self
+            .create_acme_dir_if_needed()?
+            .join(ACME_ACCOUNT_FILE_NAME);
+
+        if account_path.exists() {
+            let credentials = tokio::fs::read_to_string(&account_path)
+                .await
+                .change_context(AcmeError::FileReadingFailed)?;
+            let credentials: AccountCredentials =
+                serde_json::from_str(&credentials).change_context(AcmeError::InvalidInput)?;
+            return Account::from_credentials_and_http(
+                credentials,
+                Box::new(AcmeHttpClient::new(acme_config)?),
+            )
+            .await
+            .change_context(AcmeError::Account);
+        }
+
+        info!("Creating new ACME account");
+        let contact: Vec<String> = acme_config
+            .contact_email
+            .iter()
+            .map(|email| format!("mailto:{}", email))
+            .collect();
+        let contact: Vec<&str> = contact.iter().map(|c| c.as_str()).collect();
+        let (account, credentials) = Account::create_with_http(
+            &NewAccount {
+                contact: &contact,
+                terms_of_service_agreed: true,
+                only_return_existing: false,
+            },
+            acme_config.directory_url.as_str(),
+            None,
+            Box::new(AcmeHttpClient::new(acme_config)?),
+        )
+        .await
+        .change_context(AcmeError::Account)?;
+
+        let credentials =
+            serde_json::to_string_pretty(&credentials).change_context(AcmeError::InvalidInput)?;
+        write_private_files(&[(&account_path, credentials.as_bytes())]).await?;
+
+        Ok(account)
+    }
+
+    fn create_acme_dir_if_needed(&self) -> Result<PathBuf, AcmeError> {
+        let acme_dir = self.config.storage_dir().join(ACME_DIR_NAME);
+        if !acme_dir.exists() {
+            info!("Creating ACME directory");
+            std::fs::create_dir(&acme_dir)
+                .change_context(AcmeError::FileWritingFailed)
+                .attach_printable(acme_dir.display().to_string())?;
+        }
+        Ok(acme_dir)
+    }
+
+    fn acme_config(&self) -> Result<(&TlsConfig, &AcmeConfig), AcmeError> {
+        let tls_config = self
+            .config
+            .tls_config()
+            .ok_or(AcmeError::AcmeConfigMissing.report())?;
+        let acme_config = tls_config
+            .acme
+            .as_ref()
+            .ok_or(AcmeError::AcmeConfigMissing.report())?;
+        Ok((tls_config, acme_config))
+    }
+}
+
+/// HTTP client for ACME server connections. Operating system root
+/// certificates and the optional ACME CA certificates are trusted.
+struct AcmeHttpClient {
+    client: reqwest::Client,
+}
+
+impl AcmeHttpClient {
+    fn new(acme_config: &AcmeConfig) -> Result<Self, AcmeError> {
+        let mut builder = reqwest::ClientBuilder::new();
+        if let Some(ca_certificate) = &acme_config.ca_certificate {
+            let certs =
+                load_root_certificates(ca_certificate).change_context(AcmeError::HttpClient)?;
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        let client = builder.build().change_context(AcmeError::HttpClient)?;
+        Ok(Self { client })
+    }
+}
+
+impl HttpClient for AcmeHttpClient {
+    fn request(
+        &self,
+        request: http::Request<Full<Bytes>>,
+    ) -> Pin<Box<dyn Future<Output = std::result::Result<BytesResponse, instant_acme::Error>> + Send>>
+    {
+        let client = self.client.clone();
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let body = match body.collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(infallible) => match infallible {},
+            };
+            let response = client
+                .request(parts.method, parts.uri.to_string())
+                .headers(parts.headers)
+                .body(body)
+                .send()
+                .await
+                .map_err(|e| instant_acme::Error::Other(Box::new(e)))?;
+            Ok(BytesResponse::from(http::Response::from(response)))
+        })
+    }
+}
+
+/// Expiry time of the first certificate in a PEM file.
+fn certificate_expiry_time(cert_path: &Path) -> Result<OffsetDateTime, AcmeError> {
+    let data = std::fs::read(cert_path).change_context(AcmeError::FileReadingFailed)?;
+    let cert = rustls_pemfile::certs(&mut data.as_slice())
+        .next()
+        .ok_or(AcmeError::InvalidInput.report())?
+        .change_context(AcmeError::InvalidInput)?;
+    let (_, cert) =
+        x509_parser::parse_x509_certificate(&cert).change_context(AcmeError::InvalidInput)?;
+    Ok(cert.validity().not_after.to_datetime())
+}
+
+/// Write files which only the manager can read. All files are first
+/// written to temporary files which are then renamed, so that files are
+/// never partially written.
+async fn write_private_files(files: &[(&Path, &[u8])]) -> Result<(), AcmeError> {
+    let mut tmp_paths = vec![];
+    for (path, data) in files {
+        let mut tmp_path = OsString::from(path.as_os_str());
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(&tmp_path)
+            .await
+            .change_context(AcmeError::FileWritingFailed)
+            .attach_printable_lazy(|| tmp_path.display().to_string())?;
+        // Mode is not changed if the file already exists.
+        file.set_permissions(Permissions::from_mode(0o600))
+            .await
+            .change_context(AcmeError::FileWritingFailed)?;
+        file.write_all(data)
+            .await
+            .change_context(AcmeError::FileWritingFailed)?;
+        file.sync_all()
+            .await
+            .change_context(AcmeError::FileWritingFailed)?;
+        tmp_paths.push(tmp_path);
+    }
+
+    for ((path, _), tmp_path) in files.iter().zip(tmp_paths) {
+        tokio::fs::rename(&tmp_path, path)
+            .await
+            .change_context(AcmeError::FileWritingFailed)
+            .attach_printable_lazy(|| path.display().to_string())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use axum::{
+        extract::State,
+        http::{header, StatusCode},
+        response::{IntoResponse, Response},
+        routing::{get, post},
+        Json, Router,
+    };
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use hyper::body::Incoming;
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use rcgen::{
+        BasicConstraints, CertificateParams, CertificateSigningRequestParams, DnType, IsCa, KeyPair,
+    };
+    use serde_json::{json, Value};
+    use tokio::{net::TcpListener, sync::watch};
+    use tokio_rustls::{
+        rustls::{
+            pki_types::{CertificateSigningRequestDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+            ServerConfig,
+        },
+        TlsAcceptor,
+    };
+    use tower::Service;
+
+    use super::*;
+    use crate::config::load_test_config;
+
+    const TOKEN: &str = "token1";
+
+    struct MockAcmeServer {
+        base_url: String,
+        ca_cert: rcgen::Certificate,
+        ca_key: KeyPair,
+        challenges: Arc<AcmeChallenges>,
+        challenge_valid: AtomicBool,
+        cert_chain: std::sync::Mutex<Option<String>>,
+        validity_days: AtomicI64,
+        issued_count: AtomicUsize,
+    }
+
+    impl MockAcmeServer {
+        fn order(&self) -> Value {
+            let status = if self.cert_chain.lock().unwrap().is_some() {
+                "valid"
+            } else if self.challenge_valid.load(Ordering::SeqCst) {
+                "ready"
+            } else {
+                "pending"
+            };
+            json!({
+                "status": status,
+                "authorizations": [format!("{}/authz/1", self.base_url)],
+                "finalize": format!("{}/finalize/1", self.base_url),
+                "certificate": format!("{}/cert/1", self.base_url),
+            })
+        }
+
+        fn challenge(&self) -> Value {
+            let status = if self.challenge_valid.load(Ordering::SeqCst) {
+                "valid"
+            } else {
+                "pending"
+            };
+            json!({
+                "type": "http-01",
+                "url": format!("{}/challenge/1", self.base_url),
+                "token": TOKEN,
+                "status": status,
+            })
+        }
+    }
+
+    type MockState = State<Arc<MockAcmeServer>>;
+
+    fn acme_response(status: StatusCode, location: Option<String>, body: Value) -> Response {
+        let mut response = (status, Json(body)).into_response();
+        response
+            .headers_mut()
+            .insert("Replay-Nonce", "nonce".parse().unwrap());
+        if let Some(location) = location {
+            response
+                .headers_mut()
+                .insert(header::LOCATION, location.parse().unwrap());
+        }
+        response
+    }
+
+    fn jws_payload(body: &Value) -> Value {
+        let payload = URL_SAFE_NO_PAD
+            .decode(body["payload"].as_str().unwrap())
+            .unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    async fn directory(State(s): MockState) -> Response {
+        acme_response(
+            StatusCode::OK,
+            None,
+            json!({
+                "newNonce": format!("{}/nonce", s.base_url),
+                "newAccount": format!("{}/account", s.base_url),
+                "newOrder": format!("{}/order", s.base_url),
+            }),
+        )
+    }
+
+    async fn nonce() -> Response {
+        acme_response(StatusCode::OK, None, json!({}))
+    }
+
+    async fn new_account(State(s): MockState) -> Response {
+        let location = format!("{}/account/1", s.base_url);
+        acme_response(
+            StatusCode::CREATED,
+            Some(location),
+            json!({ "status": "valid" }),
+        )
+    }
+
+    async fn new_order(State(s): MockState) -> Response {
+        s.challenge_valid.store(false, Ordering::SeqCst);
+        *s.cert_chain.lock().unwrap() = None;
+        let location = format!("{}/order/1", s.base_url);
+        acme_response(StatusCode::CREATED, Some(location), s.order())
+    }
+
+    async fn order(State(s): MockState) -> Response {
+        acme_response(StatusCode::OK, None, s.order())
+    }
+
+    async fn authorization(State(s): MockState) -> Response {
+        let status = if s.challenge_valid.load(Ordering::SeqCst) {
+            "valid"
+        } else {
+            "pending"
+        };
+        acme_response(
+            StatusCode::OK,
+            None,
+            json!({
+                "identifier": { "type": "dns", "value": "example.com" },
+                "status": status,
+                "challenges": [s.challenge()],
+            }),
+        )
+    }
+
+    async fn challenge(State(s): MockState) -> Response {
+        // Real ACME server would make HTTP request to the domain.
+        let key_authorization = s.challenges.key_authorization(TOKEN).await;
+        let valid = key_authorization.is_some_and(|k| k.starts_with(&format!("{}.", TOKEN)));
+        s.challenge_valid.store(valid, Ordering::SeqCst);
+        acme_response(StatusCode::OK, None, s.challenge())
+    }
+
+    async fn finalize(State(s): MockState, Json(body): Json<Value>) -> Response {
+        let csr = URL_SAFE_NO_PAD
+            .decode(jws_payload(&body)["csr"].as_str().unwrap())
+            .unwrap();
+        let mut csr =
+            CertificateSigningRequestParams::from_der(&CertificateSigningRequestDer::from(csr))
+                .unwrap();
+        let validity_days = s.validity_days.load(Ordering::SeqCst);
+        csr.params.not_before = OffsetDateTime::now_utc() - time::Duration::days(1);
+        csr.params.not_after = OffsetDateTime::now_utc() + time::Duration::days(validity_days);
+        let cert = csr.signed_by(&s.ca_cert, &s.ca_key).unwrap();
+        *s.cert_chain.lock().unwrap() = Some(format!("{}{}", cert.pem(), s.ca_cert.pem()));
+        s.issued_count.fetch_add(1, Ordering::SeqCst);
+        acme_response(StatusCode::OK, None, s.order())
+    }
+
+    async fn certificate(State(s): MockState) -> Response {
+        let mut response = s
+            .cert_chain
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_default()
+            .into_response();
+        response
+            .headers_mut()
+            .insert("Replay-Nonce", "nonce".parse().unwrap());
+        response
+    }
+
+    async fn start_mock_acme_server(
+        challenges: Arc<AcmeChallenges>,
+        ca_cert_path: &Path,
+    ) -> Arc<MockAcmeServer> {
+        let mut ca_params = CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "Mock ACME CA");
+        let ca_key = KeyPair::generate().unwrap();
+        let ca_cert = ca_params.self_signed(&ca_key).unwrap();
+        std::fs::write(ca_cert_path, ca_cert.pem()).unwrap();
+
+        let server_key = KeyPair::generate().unwrap();
+        let mut server_params = CertificateParams::new(vec!["127.0.0.1".to_string()]).unwrap();
+        server_params
+            .distinguished_name
+            .push(DnType::CommonName, "Mock ACME server");
+        let server_cert = server_params
+            .signed_by(&server_key, &ca_cert, &ca_key)
+            .unwrap();
+        let tls_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![server_cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(server_key.serialize_der())),
+            )
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(tls_config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let server = Arc::new(MockAcmeServer {
+            base_url: format!("https://{}", listener.local_addr().unwrap()),
+            ca_cert,
+            ca_key,
+            challenges,
+            challenge_valid: AtomicBool::new(false),
+            cert_chain: std::sync::Mutex::new(None),
+            validity_days: AtomicI64::new(90),
+            issued_count: AtomicUsize::new(0),
+        });
+
+        let router = Router::new()
+            .route("/directory", get(directory))
+            .route("/nonce", get(nonce))
+            .route("/account", post(new_account))
+            .route("/order", post(new_order))
+            .route("/order/1", post(order))
+            .route("/authz/1", post(authorization))
+            .route("/challenge/1", post(challenge))
+            .route("/finalize/1", post(finalize))
+            .route("/cert/1", post(certificate))
+            .with_state(server.clone());
+
+        tokio::spawn(async move {
+            loop {
+                let (tcp_stream, _) = listener.accept().await.unwrap();
+                let acceptor = acceptor.clone();
+                let router = router.clone();
+                tokio::spawn(async move {
+                    let Ok(tls_stream) = acceptor.accept(tcp_stream).await else {
+                        return;
+                    };
+                    let service =
+                        hyper::service::service_fn(move |request: hyper::Request<Incoming>| {
+                            router.clone().call(request)
+                        });
+                    let _ = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+                        .serve_connection(TokioIo::new(tls_stream), service)
+                        .await;
+                });
+            }
+        });
+
+        server
+    }
+
+    fn file_mode(path: &Path) -> u32 {
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[tokio::test]
+    async fn certificate_is_issued_and_renewed_from_acme_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let ca_cert_path = dir.path().join("acme-ca.crt");
+
+        let challenges = Arc::new(AcmeChallenges::default());
+        let server = start_mock_acme_server(challenges.clone(), &ca_cert_path).await;
+
+        let config = load_test_config(
+            dir.path(),
+            &format!(
+                r#"
+[tls]
+root_certificate = "{ca_cert}"
+
+[tls.acme]
+directory_url = "{base_url}/directory"
+domains = ["example.com"]
+ca_certificate = "{ca_cert}"
+"#,
+                ca_cert = ca_cert_path.display(),
+                base_url = server.base_url,
+            ),
+        );
+        let config = Arc::new(config);
+        let initial_tls_config = config.public_api_tls_config().unwrap().clone();
+        let (tls_config_sender, tls_config_receiver) = watch::channel(initial_tls_config);
+        let manager = AcmeManager {
+            config,
+            challenges,
+            tls_config_sender,
+        };
+
+        // New certificate which expires before the renewal margin.
+        server.validity_days.store(10, Ordering::SeqCst);
+        manager.renew_certificate_if_needed().await.unwrap();
+        assert_eq!(server.issued_count.load(Ordering::SeqCst), 1);
+        assert!(tls_config_receiver.has_changed().unwrap());
+
+        // Renewal
+        server.validity_days.store(90, Ordering::SeqCst);
+        manager.renew_certificate_if_needed().await.unwrap();
+        assert_eq!(server.issued_count.load(Ordering::SeqCst), 2);
+
+        // Certificate is up to date
+        manager.renew_certificate_if_needed().await.unwrap();
+        assert_eq!(server.issued_count.load(Ordering::SeqCst), 2);
+
+        let acme_dir = manager.config.storage_dir().join(ACME_DIR_NAME);
+        for file in [
+            crate::config::file::ACME_CERT_FILE_NAME,
+            crate::config::file::ACME_KEY_FILE_NAME,
+            ACME_ACCOUNT_FILE_NAME,
+        ] {
+            assert_eq!(file_mode(&acme_dir.join(file)), 0o600, "{}", file);
+        }
+        assert!(!acme_dir.join("server.crt.tmp").exists());
+    }
+}
@@ -1,15 +1,23 @@
 use std::sync::Arc;
 
-use axum::Router;
+use axum::{middleware, routing::get, Router};
 
 use self::private_routers::PrivateRoutes;
 use super::{
+    acme::AcmeChallenges,
+    backend_controller::BackendController,
     build::BuildManagerHandle,
     client::{ApiClient, ApiManager},
+    reboot::RebootManagerHandle,
+    reload::ConfigReloader,
+    state::StateStorage,
     update::UpdateManagerHandle,
 };
 use crate::{
-    api::{GetApiManager, GetBuildManager, GetConfig, GetUpdateManager},
+    api::{
+        self, GetAcmeChallenges, GetApiManager, GetBackendController, GetBuildManager, GetConfig,
+        GetConfigReloader, GetRebootManager, GetStateStorage, GetUpdateManager,
+    },
     config::Config,
 };
 
@@ -21,6 +29,11 @@ pub struct AppState {
     api: Arc<ApiClient>,
     build_manager: Arc<BuildManagerHandle>,
     update_manager: Arc<UpdateManagerHandle>,
+    acme_challenges: Arc<AcmeChallenges>,
+    config_reloader: Arc<ConfigReloader>,
+    backend_controller: BackendController,
+    reboot_manager: RebootManagerHandle,
+    state_storage: Arc<StateStorage>,
 }
 
 impl GetConfig for AppState {
@@ -47,22 +60,63 @@ impl GetApiManager for AppState {
     }
 }
 
+impl GetAcmeChallenges for AppState {
+    fn acme_challenges(&self) -> &AcmeChallenges {
+        &self.acme_challenges
+    }
+}
+
+impl GetConfigReloader for AppState {
+    fn config_reloader(&self) -> &ConfigReloader {
+        &self.config_reloader
+    }
+}
+
+impl GetBackendController for AppState {
+    fn backend_controller(&self) -> &BackendController {
+        &self.backend_controller
+    }
+}
+
+impl GetRebootManager for AppState {
+    fn reboot_manager(&self) -> &RebootManagerHandle {
+        &self.reboot_manager
+    }
+}
+
+impl GetStateStorage for AppState {
+    fn state_storage(&self) -> &StateStorage {
+        &self.state_storage
+    }
+}
+
 pub struct App {
     pub state: AppState,
 }
 
 impl App {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         config: Arc<Config>,
         api_client: Arc<ApiClient>,
         build_manager: Arc<BuildManagerHandle>,
         update_manager: Arc<UpdateManagerHandle>,
+        acme_challenges: Arc<AcmeChallenges>,
+        config_reloader: Arc<ConfigReloader>,
+        backend_controller: BackendController,
+        reboot_manager: RebootManagerHandle,
+        state_storage: Arc<StateStorage>,
     ) -> Self {
         let state = AppState {
             config: config.clone(),
             api: api_client.clone(),
             build_manager,
             update_manager,
+            acme_challenges,
+            config_reloader,
+            backend_controller,
+            reboot_manager,
+            state_storage,
         };
 
         Self { state }
@@ -73,7 +127,34 @@ impl App {
     }
 
     pub fn create_manager_server_router(&self) -> Router {
-        let public = Router::new();
-        public.merge(PrivateRoutes::new(self.state.clone()).private_manager_server_router())
+        let public = Router::new().merge(self.create_acme_challenge_router());
+        public
+            .merge(PrivateRoutes::new(self.state.clone()).private_manager_server_router())
+            .layer(middleware::from_fn(api::utils::record_api_request_metrics))
+    }
+
+    /// Router for Prometheus metrics. API key is required.
+    pub fn create_metrics_router(&self) -> Router {
+        PrivateRoutes::new(self.state.clone()).private_metrics_router()
+    }
+
+    /// Router for Prometheus metrics without API key. Use this only
+    /// for localhost only servers.
+    pub fn create_localhost_metrics_router(&self) -> Router {
+        Router::new().route(
+            api::metrics::PATH_GET_METRICS,
+            get(api::metrics::get_metrics),
+        )
+    }
+
+    /// Router for ACME HTTP-01 challenge responses. This is public.
+    pub fn create_acme_challenge_router(&self) -> Router {
+        Router::new().route(
+            api::acme::PATH_GET_ACME_CHALLENGE,
+            get({
+                let state = self.state.clone();
+                move |param1| api::acme::get_acme_challenge(param1, state)
+            }),
+        )
     }
 }
@@ -68,6 +68,73 @@ impl PrivateRoutes {
                     }
                 }),
             )
+            .route(
+                api::manager::PATH_GET_BACKEND_STATUS,
+                get({
+                    let state = self.state.clone();
+                    move |param1| api::manager::get_backend_status(param1, state)
+                }),
+            )
+            .route(
+                api::manager::PATH_GET_UPDATE_STATUS,
+                get({
+                    let state = self.state.clone();
+                    move |param1| api::manager::get_update_status(param1, state)
+                }),
+            )
+            .route(
+                api::manager::PATH_GET_REBOOT_STATUS,
+                get({
+                    let state = self.state.clone();
+                    move |param1| api::manager::get_reboot_status(param1, state)
+                }),
+            )
+            .route(
+                api::manager::PATH_POST_SCHEDULE_REBOOT,
+                post({
+                    let state = self.state.clone();
+                    move |param1| api::manager::post_schedule_reboot(param1, state)
+                }),
+            )
+            .route(
+                api::manager::PATH_POST_REBOOT_NOW,
+                post({
+                    let state = self.state.clone();
+                    move |param1| api::manager::post_reboot_now(param1, state)
+                }),
+            )
+            .route(
+                api::manager::PATH_POST_CANCEL_REBOOT,
+                post({
+                    let state = self.state.clone();
+                    move |param1| api::manager::post_cancel_reboot(param1, state)
+                }),
+            )
+            .route(
+                api::manager::PATH_POST_ACQUIRE_REBOOT_LEASE,
+                post({
+                    let state = self.state.clone();
+                    move |param1, param2| {
+                        api::manager::post_acquire_reboot_lease(param1, param2, state)
+                    }
+                }),
+            )
+            .route(
+                api::manager::PATH_POST_RELEASE_REBOOT_LEASE,
+                post({
+                    let state = self.state.clone();
+                    move |param1, param2| {
+                        api::manager::post_release_reboot_lease(param1, param2, state)
+                    }
+                }),
+            )
+            .route(
+                api::manager::PATH_POST_RELOAD_CONFIG,
+                post({
+                    let state = self.state.clone();
+                    move |param1| api::manager::post_reload_config(param1, state)
+                }),
+            )
             .route(
                 api::manager::PATH_GET_SOFTWARE_INFO,
                 get({
@@ -79,14 +146,21 @@ impl PrivateRoutes {
                 api::manager::PATH_GET_SYSTEM_INFO,
                 get({
                     let state = self.state.clone();
-                    move |param1| api::manager::get_system_info(param1, state)
+                    move |param1, param2| api::manager::get_system_info(param1, param2, state)
                 }),
             )
             .route(
                 api::manager::PATH_GET_SYSTEM_INFO_ALL,
                 get({
                     let state = self.state.clone();
-                    move |param1| api::manager::get_system_info_all(param1, state)
+                    move |param1, param2| api::manager::get_system_info_all(param1, param2, state)
+                }),
+            )
+            .route(
+                api::manager::PATH_GET_LOGS,
+                get({
+                    let state = self.state.clone();
+                    move |param1, param2| api::manager::get_logs(param1, param2, state)
                 }),
             )
             .route_layer({
@@ -100,4 +174,21 @@ impl PrivateRoutes {
 
         Router::new().merge(private)
     }
+
+    /// Prometheus metrics router which requires API key.
+    pub fn private_metrics_router(&self) -> Router {
+        Router::new()
+            .route(
+                api::metrics::PATH_GET_METRICS,
+                get(api::metrics::get_metrics),
+            )
+            .route_layer({
+                middleware::from_fn({
+                    let state = self.state.clone();
+                    move |addr, req, next| {
+                        api::utils::authenticate_with_api_key(state.clone(), addr, req, next)
+                    }
+                })
+            })
+    }
 }
@@ -1,11 +1,29 @@
-//! Start and stop backend
+//! Start, stop and restart backend
 
-use std::process::ExitStatus;
+use std::{
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    sync::Arc,
+    time::Duration,
+};
 
 use error_stack::{Result, ResultExt};
+use futures::future::BoxFuture;
+use manager_model::{BackendStatus, UpdateStep};
 use tokio::process::Command;
+use tracing::{info, warn};
+use url::Url;
 
-use crate::config::Config;
+use super::{
+    backend_supervisor::BackendSupervisorHandle,
+    metrics::{BackendAction, METRICS},
+    systemd::SystemdController,
+    update::status::UpdateStatusTracker,
+};
+use crate::config::{file::BackendControllerType, Config};
+
+/// Extra time for the stop sequence when the manager quits.
+const SHUTDOWN_TIMEOUT_MARGIN: Duration = Duration::from_secs(10);
 
 #[derive(thiserror::Error, Debug)]
 pub enum ControllerError {
@@ -20,44 +38,435 @@ pub enum ControllerError {
 
     #[error("Command failed with exit status: {0}")]
     CommandFailed(ExitStatus),
+
+    #[error("Backend supervisor failed")]
+    SupervisorFailed,
+
+    #[error("Systemd unit control failed")]
+    SystemdFailed,
+}
+
+/// How the service was stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopResult {
+    Stopped,
+    NotRunning,
+    /// Service did not quit before the stop timeout.
+    Killed,
+}
+
+/// Controls a service like the backend.
+///
+/// Methods return boxed futures so that the implementation can be
+/// selected at runtime.
+pub trait ServiceController: std::fmt::Debug + Send + Sync {
+    fn start(&self) -> BoxFuture<'_, Result<(), ControllerError>>;
+    fn stop(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>>;
+    /// Returns how the previous service instance was stopped.
+    fn restart(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>>;
+    /// Process details are available only if the implementation
+    /// supports those.
+    fn status(&self) -> BoxFuture<'_, Result<BackendStatus, ControllerError>>;
+}
+
+#[derive(Debug, Clone)]
+struct DrainHook {
+    url: Url,
+    timeout: Duration,
 }
 
-pub struct BackendController<'a> {
-    config: &'a Config,
+/// Backend controller which runs the backend stop sequence, logs the
+/// steps and records failed actions to metrics.
+///
+/// Steps are also added to the update status if it is available.
+#[derive(Debug, Clone)]
+pub struct BackendController {
+    controller: Arc<dyn ServiceController>,
+    drain: Option<DrainHook>,
+    stop_timeout: Option<Duration>,
 }
 
-impl<'a> BackendController<'a> {
-    pub fn new(config: &'a Config) -> Self {
-        Self { config }
+impl BackendController {
+    pub fn new(controller: Arc<dyn ServiceController>, config: &Config) -> Self {
+        let drain = config.backend_stop().and_then(|stop| {
+            stop.drain_url.clone().map(|url| DrainHook {
+                url,
+                timeout: stop.drain_timeout(),
+            })
+        });
+        Self {
+            controller,
+            drain,
+            stop_timeout: config.backend_stop_timeout(),
+        }
     }
 
-    pub async fn start_backend(&self) -> Result<(), ControllerError> {
-        let status = Command::new("sudo")
-            .arg(self.config.script_locations().start_backend())
-            .status()
+    /// Select the controller using the backend_controller config value.
+    pub fn from_config(config: &Config, supervisor: BackendSupervisorHandle) -> Self {
+        let controller: Arc<dyn ServiceController> = match config.backend_controller_type() {
+            BackendControllerType::Script => Arc::new(ScriptController::new(config)),
+            BackendControllerType::Process => Arc::new(supervisor),
+            BackendControllerType::Systemd => Arc::new(SystemdController::new(
+                config
+                    .backend_systemd_unit()
+                    .unwrap_or_default()
+                    .to_string(),
+            )),
+        };
+        Self::new(controller, config)
+    }
+
+    /// Maximum time which the stop sequence should take. None if the
+    /// stop timeout is not configured for the stop script.
+    pub fn shutdown_timeout(&self) -> Option<Duration> {
+        let drain_timeout = self
+            .drain
+            .as_ref()
+            .map(|drain| drain.timeout)
+            .unwrap_or_default();
+        self.stop_timeout
+            .map(|stop_timeout| drain_timeout + stop_timeout + SHUTDOWN_TIMEOUT_MARGIN)
+    }
+
+    pub async fn start_backend(
+        &self,
+        status: Option<&UpdateStatusTracker>,
+    ) -> Result<(), ControllerError> {
+        Self::step(status, UpdateStep::BackendStart, "Starting backend");
+        let result = self.controller.start().await;
+        if result.is_ok() {
+            Self::step(status, UpdateStep::BackendStart, "Backend started");
+        } else {
+            METRICS.backend_control_failed(BackendAction::Start);
+        }
+        result
+    }
+
+    /// Run the drain hook and stop the backend.
+    pub async fn stop_backend(
+        &self,
+        status: Option<&UpdateStatusTracker>,
+    ) -> Result<(), ControllerError> {
+        self.drain(status).await;
+        Self::step(status, UpdateStep::BackendStop, "Stopping backend");
+        let result = self.controller.stop().await;
+        match &result {
+            Ok(stop_result) => self.stop_result_step(status, *stop_result),
+            Err(_) => METRICS.backend_control_failed(BackendAction::Stop),
+        }
+        result.map(|_| ())
+    }
+
+    /// Run the drain hook and restart the backend.
+    pub async fn restart_backend(
+        &self,
+        status: Option<&UpdateStatusTracker>,
+    ) -> Result<(), ControllerError> {
+        self.drain(status).await;
+        Self::step(status, UpdateStep::BackendStop, "Restarting backend");
+        let result = self.controller.restart().await;
+        match &result {
+            Ok(stop_result) => {
+                self.stop_result_step(status, *stop_result);
+                Self::step(status, UpdateStep::BackendStart, "Backend started");
+            }
+            Err(_) => METRICS.backend_control_failed(BackendAction::Restart),
+        }
+        result.map(|_| ())
+    }
+
+    pub async fn backend_status(&self) -> Result<BackendStatus, ControllerError> {
+        self.controller.status().await
+    }
+
+    /// Drain failures are logged and the stop sequence continues.
+    async fn drain(&self, status: Option<&UpdateStatusTracker>) {
+        let Some(drain) = &self.drain else {
+            return;
+        };
+
+        Self::step(
+            status,
+            UpdateStep::BackendDrain,
+            format!("Sending drain request to {}", drain.url),
+        );
+        let result = reqwest::Client::new()
+            .post(drain.url.clone())
+            .timeout(drain.timeout)
+            .send()
             .await
-            .change_context(ControllerError::ProcessWaitFailed)?;
+            .and_then(|response| response.error_for_status());
+        let message = match result {
+            Ok(_) => "Backend drain completed".to_string(),
+            Err(e) if e.is_timeout() => {
+                warn!("Backend drain request timed out");
+                format!(
+                    "Backend drain timed out after {} seconds",
+                    drain.timeout.as_secs()
+                )
+            }
+            Err(e) => {
+                warn!("Backend drain request failed. Error: {}", e);
+                format!("Backend drain failed: {}", e)
+            }
+        };
+        Self::step(status, UpdateStep::BackendDrain, message);
+    }
 
-        if !status.success() {
-            tracing::error!("Start backend failed with status: {:?}", status);
-            return Err(ControllerError::CommandFailed(status).into());
+    fn stop_result_step(&self, status: Option<&UpdateStatusTracker>, stop_result: StopResult) {
+        match stop_result {
+            StopResult::Stopped => {
+                Self::step(status, UpdateStep::BackendStop, "Backend stopped");
+            }
+            StopResult::NotRunning => {
+                Self::step(status, UpdateStep::BackendStop, "Backend was not running");
+            }
+            StopResult::Killed => {
+                Self::step(
+                    status,
+                    UpdateStep::BackendKill,
+                    format!(
+                        "Backend did not quit in {} seconds and it was killed",
+                        self.stop_timeout.unwrap_or_default().as_secs()
+                    ),
+                );
+            }
         }
+    }
 
-        Ok(())
+    fn step(status: Option<&UpdateStatusTracker>, step: UpdateStep, message: impl Into<String>) {
+        let message = message.into();
+        info!("{}", message);
+        if let Some(status) = status {
+            status.step(step, message);
+        }
     }
+}
 
-    pub async fn stop_backend(&self) -> Result<(), ControllerError> {
+/// Uses start-backend.sh and stop-backend.sh scripts with sudo.
+#[derive(Debug)]
+pub struct ScriptController {
+    start_script: PathBuf,
+    stop_script: PathBuf,
+    stop_timeout: Option<Duration>,
+}
+
+impl ScriptController {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            start_script: config.script_locations().start_backend().to_path_buf(),
+            stop_script: config.script_locations().stop_backend().to_path_buf(),
+            stop_timeout: config.backend_stop_timeout(),
+        }
+    }
+
+    async fn run_script(script: &Path, args: &[String]) -> Result<(), ControllerError> {
         let status = Command::new("sudo")
-            .arg(self.config.script_locations().stop_backend())
+            .arg(script)
+            .args(args)
             .status()
             .await
             .change_context(ControllerError::ProcessWaitFailed)?;
 
         if !status.success() {
-            tracing::error!("Start backend failed with status: {:?}", status);
+            tracing::error!(
+                "Script {} failed with status: {:?}",
+                script.display(),
+                status
+            );
             return Err(ControllerError::CommandFailed(status).into());
         }
 
         Ok(())
     }
 }
+
+impl ServiceController for ScriptController {
+    fn start(&self) -> BoxFuture<'_, Result<(), ControllerError>> {
+        Box::pin(Self::run_script(&self.start_script, &[]))
+    }
+
+    /// The stop timeout in seconds is the first argument of the stop
+    /// script if it is configured. The script is responsible for
+    /// killing the backend after the timeout, because the manager can
+    /// not signal the backend started using sudo.
+    fn stop(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+        Box::pin(async {
+            let args: Vec<String> = self
+                .stop_timeout
+                .map(|timeout| timeout.as_secs().to_string())
+                .into_iter()
+                .collect();
+            Self::run_script(&self.stop_script, &args).await?;
+            Ok(StopResult::Stopped)
+        })
+    }
+
+    /// Scripts do not support restarting, so the backend is stopped
+    /// and started.
+    fn restart(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+        Box::pin(async {
+            let stop_result = self.stop().await?;
+            self.start().await?;
+            Ok(stop_result)
+        })
+    }
+
+    fn status(&self) -> BoxFuture<'_, Result<BackendStatus, ControllerError>> {
+        Box::pin(async { Ok(BackendStatus::default()) })
+    }
+}
+
+/// Backend is a child process of the manager.
+impl ServiceController for BackendSupervisorHandle {
+    fn start(&self) -> BoxFuture<'_, Result<(), ControllerError>> {
+        Box::pin(async {
+            self.start_backend()
+                .await
+                .change_context(ControllerError::SupervisorFailed)
+        })
+    }
+
+    fn stop(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+        Box::pin(async {
+            self.stop_backend()
+                .await
+                .change_context(ControllerError::SupervisorFailed)
+        })
+    }
+
+    fn restart(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+        Box::pin(async {
+            let stop_result = ServiceController::stop(self).await?;
+            ServiceController::start(self).await?;
+            Ok(stop_result)
+        })
+    }
+
+    fn status(&self) -> BoxFuture<'_, Result<BackendStatus, ControllerError>> {
+        Box::pin(async {
+            BackendSupervisorHandle::status(self)
+                .await
+                .change_context(ControllerError::SupervisorFailed)
+        })
+    }
+}
+
+impl ServiceController for SystemdController {
+    fn start(&self) -> BoxFuture<'_, Result<(), ControllerError>> {
+        Box::pin(async {
+            SystemdController::start(self)
+                .await
+                .change_context(ControllerError::SystemdFailed)
+        })
+    }
+
+    /// Systemd sends SIGTERM and kills the process if the unit's stop
+    /// timeout expires.
+    fn stop(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+        Box::pin(async {
+            SystemdController::stop(self)
+                .await
+                .change_context(ControllerError::SystemdFailed)?;
+            Ok(StopResult::Stopped)
+        })
+    }
+
+    fn restart(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+        Box::pin(async {
+            SystemdController::restart(self)
+                .await
+                .change_context(ControllerError::SystemdFailed)?;
+            Ok(StopResult::Stopped)
+        })
+    }
+
+    fn status(&self) -> BoxFuture<'_, Result<BackendStatus, ControllerError>> {
+        Box::pin(async {
+            let status = SystemdController::status(self)
+                .await
+                .change_context(ControllerError::SystemdFailed)?;
+            Ok(BackendStatus {
+                running: status.is_active(),
+                pid: status.main_pid,
+                uptime_seconds: status.active_duration.map(|d| d.as_secs()),
+                last_exit_code: status.last_exit_code,
+                systemd_unit: Some(self.unit().to_string()),
+                active_state: Some(status.active_state),
+                sub_state: Some(status.sub_state),
+                ..Default::default()
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod test_utils {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    /// Records called actions and keeps track of running state.
+    #[derive(Debug, Default)]
+    pub struct FakeServiceController {
+        actions: Mutex<Vec<&'static str>>,
+        running: Mutex<bool>,
+    }
+
+    impl FakeServiceController {
+        pub fn new(running: bool) -> Self {
+            Self {
+                running: Mutex::new(running),
+                ..Default::default()
+            }
+        }
+
+        pub fn actions(&self) -> Vec<&'static str> {
+            self.actions.lock().unwrap().clone()
+        }
+
+        pub fn is_running(&self) -> bool {
+            *self.running.lock().unwrap()
+        }
+
+        fn record(&self, action: &'static str, running: bool) -> bool {
+            self.actions.lock().unwrap().push(action);
+            std::mem::replace(&mut *self.running.lock().unwrap(), running)
+        }
+
+        fn stop_result(was_running: bool) -> StopResult {
+            if was_running {
+                StopResult::Stopped
+            } else {
+                StopResult::NotRunning
+            }
+        }
+    }
+
+    impl ServiceController for FakeServiceController {
+        fn start(&self) -> BoxFuture<'_, Result<(), ControllerError>> {
+            self.record("start", true);
+            Box::pin(async { Ok(()) })
+        }
+
+        fn stop(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+            let was_running = self.record("stop", false);
+            Box::pin(async move { Ok(Self::stop_result(was_running)) })
+        }
+
+        fn restart(&self) -> BoxFuture<'_, Result<StopResult, ControllerError>> {
+            let was_running = self.record("restart", true);
+            Box::pin(async move { Ok(Self::stop_result(was_running)) })
+        }
+
+        fn status(&self) -> BoxFuture<'_, Result<BackendStatus, ControllerError>> {
+            let running = self.is_running();
+            Box::pin(async move {
+                Ok(BackendStatus {
+                    running,
+                    ..Default::default()
+                })
+            })
+        }
+    }
+}
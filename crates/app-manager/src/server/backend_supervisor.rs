@@ -0,0 +1,408 @@
+//! Run backend as a child process of the manager
+
+use std::{
+    process::{ExitStatus, Stdio},
+    sync::Arc,
+    time::Duration,
+};
+
+use error_stack::{Result, ResultExt};
+use manager_model::BackendStatus;
+use nix::{
+    sys::signal::{kill, Signal},
+    unistd::Pid,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::{sleep_until, Instant},
+};
+use tracing::{error, info, warn};
+
+use super::{backend_controller::StopResult, ServerQuitWatcher};
+use crate::{
+    config::{Config, DEFAULT_BACKEND_STOP_TIMEOUT},
+    utils::ContextExt,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum SupervisorError {
+    #[error("Backend supervisor not available")]
+    SupervisorNotAvailable,
+
+    #[error("Backend supervisor is not enabled")]
+    NotEnabled,
+
+    #[error("Process start failed")]
+    ProcessStartFailed,
+
+    #[error("Process wait failed")]
+    ProcessWaitFailed,
+
+    #[error("Process kill failed")]
+    ProcessKillFailed,
+
+    #[error("Sending signal failed")]
+    SignalFailed,
+}
+
+#[derive(Debug)]
+pub struct BackendSupervisorQuitHandle {
+    task: JoinHandle<()>,
+    // Make sure Receiver works until the manager quits.
+    _sender: mpsc::Sender<BackendSupervisorMessage>,
+}
+
+impl BackendSupervisorQuitHandle {
+    /// Supervised backend is stopped before the supervisor quits.
+    pub async fn wait_quit(self) {
+        match self.task.await {
+            Ok(()) => (),
+            Err(e) => {
+                warn!("Backend supervisor quit failed. Error: {:?}", e);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BackendSupervisorMessage {
+    Start(oneshot::Sender<Result<(), SupervisorError>>),
+    Stop(oneshot::Sender<Result<StopResult, SupervisorError>>),
+    Status(oneshot::Sender<BackendStatus>),
+}
+
+#[derive(Debug, Clone)]
+pub struct BackendSupervisorHandle {
+    sender: mpsc::Sender<BackendSupervisorMessage>,
+}
+
+impl BackendSupervisorHandle {
+    pub async fn start_backend(&self) -> Result<(), SupervisorError> {
+        self.request(BackendSupervisorMessage::Start).await?
+    }
+
+    pub async fn stop_backend(&self) -> Result<StopResult, SupervisorError> {
+        self.request(BackendSupervisorMessage::Stop).await?
+    }
+
+    pub async fn status(&self) -> Result<BackendStatus, SupervisorError> {
+        self.request(BackendSupervisorMessage::Status).await
+    }
+
+    async fn request<T>(
+        &self,
+        message: impl FnOnce(oneshot::Sender<T>) -> BackendSupervisorMessage,
+    ) -> Result<T, SupervisorError> {
+        let (sender, receiver) = oneshot::channel();
+        self.sender
+            .send(message(sender))
+            .await
+            .change_context(SupervisorError::SupervisorNotAvailable)?;
+        receiver
+            .await
+            .change_context(SupervisorError::SupervisorNotAvailable)
+    }
+}
+
+struct RunningBackend {
+    child: Child,
+    pid: Option<u32>,
+    started: Instant,
+}
+
+pub struct BackendSupervisor {
+    config: Arc<Config>,
+    receiver: mpsc::Receiver<BackendSupervisorMessage>,
+    backend: Option<RunningBackend>,
+    restart_at: Option<Instant>,
+    restart_delay: Duration,
+    restart_count: u32,
+    last_exit_code: Option<i32>,
+}
+
+impl BackendSupervisor {
+    pub fn new_manager(
+        config: Arc<Config>,
+        quit_notification: ServerQuitWatcher,
+    ) -> (BackendSupervisorQuitHandle, BackendSupervisorHandle) {
+        let (sender, receiver) = mpsc::channel(1);
+
+        let restart_delay = config
+            .backend_supervisor()
+            .map(|supervisor| supervisor.restart_min_delay())
+            .unwrap_or_default();
+
+        let manager = Self {
+            config,
+            receiver,
+            backend: None,
+            restart_at: None,
+            restart_delay,
+            restart_count: 0,
+            last_exit_code: None,
+        };
+
+        let task = tokio::spawn(manager.run(quit_notification));
+
+        let handle = BackendSupervisorHandle { sender };
+
+        let quit_handle = BackendSupervisorQuitHandle {
+            task,
+            _sender: handle.sender.clone(),
+        };
+
+        (quit_handle, handle)
+    }
+
+    pub async fn run(mut self, mut quit_notification: ServerQuitWatcher) {
+        loop {
+            tokio::select! {
+                result = Self::wait_exit(&mut self.backend) => {
+                    self.handle_unexpected_exit(result);
+                }
+                _ = Self::sleep_until_restart(self.restart_at) => {
+                    self.restart_at = None;
+                    self.restart_count += 1;
+                    info!("Restarting backend. Restart count: {}", self.restart_count);
+                    if let Err(e) = self.start() {
+                        error!("Backend restart failed. Error: {:?}", e);
+                        self.schedule_restart();
+                    }
+                }
+                message = self.receiver.recv() => {
+                    match message {
+                        Some(message) => {
+                            self.handle_message(message).await;
+                        }
+                        None => {
+                            warn!("Backend supervisor channel closed");
+                            break;
+                        }
+                    }
+                }
+                _ = quit_notification.recv() => {
+                    break;
+                }
+            }
+        }
+
+        if self.backend.is_some() {
+            info!("Stopping supervised backend");
+            if let Err(e) = self.stop().await {
+                warn!("Stopping supervised backend failed. Error: {:?}", e);
+            }
+        }
+    }
+
+    async fn handle_message(&mut self, message: BackendSupervisorMessage) {
+        match message {
+            BackendSupervisorMessage::Start(sender) => {
+                let result = self.start();
+                // Manual start resets the restart delay.
+                self.restart_at = None;
+                if let Some(supervisor) = self.config.backend_supervisor() {
+                    self.restart_delay = supervisor.restart_min_delay();
+                }
+                let _ = sender.send(result);
+            }
+            BackendSupervisorMessage::Stop(sender) => {
+                self.restart_at = None;
+                let result = self.stop().await;
+                let _ = sender.send(result);
+            }
+            BackendSupervisorMessage::Status(sender) => {
+                let _ = sender.send(self.status());
+            }
+        }
+    }
+
+    async fn wait_exit(backend: &mut Option<RunningBackend>) -> std::io::Result<ExitStatus> {
+        match backend {
+            Some(backend) => backend.child.wait().await,
+            None => futures::future::pending().await,
+        }
+    }
+
+    async fn sleep_until_restart(restart_at: Option<Instant>) {
+        match restart_at {
+            Some(restart_at) => sleep_until(restart_at).await,
+            None => futures::future::pending().await,
+        }
+    }
+
+    fn handle_unexpected_exit(&mut self, result: std::io::Result<ExitStatus>) {
+        let uptime = self
+            .backend
+            .take()
+            .map(|backend| backend.started.elapsed())
+            .unwrap_or_default();
+
+        match result {
+            Ok(status) => {
+                self.last_exit_code = status.code();
+                warn!("Backend quit unexpectedly with status: {}", status);
+            }
+            Err(e) => {
+                self.last_exit_code = None;
+                error!("Waiting backend process failed. Error: {}", e);
+            }
+        }
+
+        // Backend which was running longer than the maximum delay
+        // is restarted using the minimum delay.
+        if let Some(supervisor) = self.config.backend_supervisor() {
+            if uptime >= supervisor.restart_max_delay() {
+                self.restart_delay = supervisor.restart_min_delay();
+            }
+        }
+
+        self.schedule_restart();
+    }
+
+    fn schedule_restart(&mut self) {
+        let Some(supervisor) = self.config.backend_supervisor() else {
+            return;
+        };
+        info!(
+            "Backend will be restarted after {} seconds",
+            self.restart_delay.as_secs()
+        );
+        self.restart_at = Some(Instant::now() + self.restart_delay);
+        self.restart_delay = (self.restart_delay * 2).min(supervisor.restart_max_delay());
+    }
+
+    fn start(&mut self) -> Result<(), SupervisorError> {
+        if self.backend.is_some() {
+            info!("Supervised backend is already running");
+            return Ok(());
+        }
+
+        let (Some(update_config), Some(supervisor)) = (
+            self.config.software_update_provider(),
+            self.config.backend_supervisor(),
+        ) else {
+            return Err(SupervisorError::NotEnabled.report());
+        };
+
+        let binary = &update_config.backend_install_location;
+        let working_dir = supervisor
+            .working_dir
+            .as_deref()
+            .or_else(|| binary.parent())
+            .filter(|dir| !dir.as_os_str().is_empty());
+
+        let mut command = Command::new(binary);
+        command
+            .args(supervisor.args.iter().flatten())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = working_dir {
+            command.current_dir(dir);
+        }
+
+        let mut child = command
+            .spawn()
+            .change_context(SupervisorError::ProcessStartFailed)
+            .attach_printable_lazy(|| binary.display().to_string())?;
+
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(log_backend_output(stdout, false));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(log_backend_output(stderr, true));
+        }
+
+        let pid = child.id();
+        info!("Supervised backend started. PID: {:?}", pid);
+
+        self.backend = Some(RunningBackend {
+            child,
+            pid,
+            started: Instant::now(),
+        });
+
+        Ok(())
+    }
+
+    /// Send SIGTERM and kill the backend if it does not quit before
+    /// the stop timeout.
+    async fn stop(&mut self) -> Result<StopResult, SupervisorError> {
+        let Some(mut backend) = self.backend.take() else {
+            info!("Supervised backend is not running");
+            return Ok(StopResult::NotRunning);
+        };
+
+        let stop_timeout = self
+            .config
+            .backend_stop_timeout()
+            .unwrap_or(DEFAULT_BACKEND_STOP_TIMEOUT);
+
+        if let Some(pid) = backend.pid {
+            let pid = i32::try_from(pid).change_context(SupervisorError::SignalFailed)?;
+            kill(Pid::from_raw(pid), Signal::SIGTERM)
+                .change_context(SupervisorError::SignalFailed)?;
+        }
+
+        let (status, stop_result) =
+            match tokio::time::timeout(stop_timeout, backend.child.wait()).await {
+                Ok(status) => (
+                    status.change_context(SupervisorError::ProcessWaitFailed)?,
+                    StopResult::Stopped,
+                ),
+                Err(_) => {
+                    warn!(
+                        "Backend did not quit in {} seconds. Killing the backend",
+                        stop_timeout.as_secs()
+                    );
+                    backend
+                        .child
+                        .kill()
+                        .await
+                        .change_context(SupervisorError::ProcessKillFailed)?;
+                    let status = backend
+                        .child
+                        .wait()
+                        .await
+                        .change_context(SupervisorError::ProcessWaitFailed)?;
+                    (status, StopResult::Killed)
+                }
+            };
+
+        self.last_exit_code = status.code();
+        info!("Supervised backend stopped with status: {}", status);
+
+        Ok(stop_result)
+    }
+
+    fn status(&self) -> BackendStatus {
+        BackendStatus {
+            supervised: self.config.backend_supervisor().is_some(),
+            running: self.backend.is_some(),
+            pid: self.backend.as_ref().and_then(|backend| backend.pid),
+            uptime_seconds: self
+                .backend
+                .as_ref()
+                .map(|backend| backend.started.elapsed().as_secs()),
+            last_exit_code: self.last_exit_code,
+            restart_count: self.restart_count,
+            ..Default::default()
+        }
+    }
+}
+
+/// Backend output is written to the manager log.
+async fn log_backend_output(output: impl AsyncRead + Unpin, stderr: bool) {
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if stderr {
+            warn!("Backend: {}", line);
+        } else {
+            info!("Backend: {}", line);
+        }
+    }
+}
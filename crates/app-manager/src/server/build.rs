@@ -4,6 +4,7 @@ use std::{
     path::{Path, PathBuf},
     process::ExitStatus,
     sync::Arc,
+    time::Instant,
 };
 
 use error_stack::{Result, ResultExt};
@@ -13,7 +14,11 @@ use manager_model::{
 use tokio::{process::Command, task::JoinHandle};
 use tracing::{info, warn};
 
-use super::ServerQuitWatcher;
+use super::{
+    metrics::METRICS,
+    state::{BuildResult, StateStorage},
+    ServerQuitWatcher,
+};
 use crate::{
     config::{file::SoftwareBuilderConfig, Config},
     utils::{InProgressChannel, InProgressReceiver, InProgressSender},
@@ -122,16 +127,22 @@ impl BuildManagerHandle {
 pub struct BuildManager {
     config: Arc<Config>,
     receiver: InProgressReceiver<BuildManagerMessage>,
+    state: Arc<StateStorage>,
 }
 
 impl BuildManager {
     pub fn new_manager(
         config: Arc<Config>,
+        state: Arc<StateStorage>,
         quit_notification: ServerQuitWatcher,
     ) -> (BuildManagerQuitHandle, BuildManagerHandle) {
         let (sender, receiver) = InProgressChannel::create();
 
-        let manager = Self { config, receiver };
+        let manager = Self {
+            config,
+            receiver,
+            state,
+        };
 
         let task = tokio::spawn(manager.run(quit_notification));
 
@@ -178,30 +189,34 @@ impl BuildManager {
     }
 
     pub async fn handle_message(&self, message: &BuildManagerMessage) {
-        match message {
+        let start = Instant::now();
+        let (software, result) = match message {
             BuildManagerMessage::BuildNewBackendVersion => {
                 info!("Building backend version");
-                match self.git_refresh_backend_if_needed().await {
-                    Ok(()) => {
-                        info!("Build finished");
-                    }
-                    Err(e) => {
-                        warn!("Build failed. Error: {:?}", e);
-                    }
-                }
+                let result = self.git_refresh_backend_if_needed().await;
+                (SoftwareOptions::Backend, result)
             }
             BuildManagerMessage::BuildNewManagerVersion => {
                 info!("Building manager version");
-                match self.git_refresh_manager_if_needed().await {
-                    Ok(()) => {
-                        info!("Build finished");
-                    }
-                    Err(e) => {
-                        warn!("Build failed. Error: {:?}", e);
-                    }
-                }
+                let result = self.git_refresh_manager_if_needed().await;
+                (SoftwareOptions::Manager, result)
             }
-        }
+        };
+        METRICS.build_finished(software, start.elapsed(), result.is_ok());
+        let error = match result {
+            Ok(()) => {
+                info!("Build finished");
+                None
+            }
+            Err(e) => {
+                warn!("Build failed. Error: {:?}", e);
+                Some(format!("{:#}", e))
+            }
+        };
+        let result = BuildResult::new(software, error);
+        self.state
+            .modify(|s| s.persistent.last_build = Some(result))
+            .await;
     }
 
     pub fn create_build_dir_if_needed(&self) -> PathBuf {
@@ -1,11 +1,14 @@
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, sync::RwLock, time::Duration};
 
 use error_stack::{Result, ResultExt};
 use manager_api::{ApiKey, Configuration, ManagerApi};
-use manager_model::{BuildInfo, DataEncryptionKey, SoftwareOptions, SystemInfo};
+use manager_model::{
+    BuildInfo, DataEncryptionKey, RebootLease, SoftwareOptions, SystemInfo,
+    SystemInfoSectionsQueryParam,
+};
 use tracing::info;
 
-use crate::config::Config;
+use crate::config::{file::RebootCoordinationConfig, Config};
 
 #[derive(thiserror::Error, Debug)]
 pub enum ApiError {
@@ -32,19 +35,21 @@ pub enum ApiError {
 }
 
 fn base_client_builder(config: &Config) -> reqwest::ClientBuilder {
-    let client = reqwest::ClientBuilder::new().tls_built_in_root_certs(false);
-    if let Some(cert) = config.root_certificate() {
-        client.add_root_certificate(cert.clone())
-    } else {
-        client
-    }
+    config.root_certificates().iter().fold(
+        reqwest::ClientBuilder::new()
+            .tls_built_in_root_certs(config.use_system_root_certificates()),
+        |client, cert| client.add_root_certificate(cert.clone()),
+    )
 }
 
 #[derive(Debug)]
 pub struct ApiClient {
+    client: reqwest::Client,
+    api_key: ApiKey,
     encryption_key_provider: Option<Configuration>,
     software_update_provider: Option<Configuration>,
-    system_info_remote_managers: HashMap<String, Configuration>,
+    /// Can be changed when config is reloaded.
+    system_info_remote_managers: RwLock<HashMap<String, Configuration>>,
 }
 
 impl ApiClient {
@@ -104,9 +109,26 @@ impl ApiClient {
             }
         });
 
+        let system_info_remote_managers =
+            Self::create_system_info_remote_managers(config, &client, &api_key);
+
+        Ok(Self {
+            client,
+            api_key,
+            encryption_key_provider,
+            software_update_provider,
+            system_info_remote_managers: RwLock::new(system_info_remote_managers),
+        })
+    }
+
+    fn create_system_info_remote_managers(
+        config: &Config,
+        client: &reqwest::Client,
+        api_key: &ApiKey,
+    ) -> HashMap<String, Configuration> {
         let mut system_info_remote_managers = HashMap::new();
 
-        if let Some(info_config) = config.system_info() {
+        if let Some(info_config) = config.reloadable().system_info() {
             for service in info_config.remote_managers.iter().flatten() {
                 let url = service
                     .manager_base_url
@@ -130,11 +152,16 @@ impl ApiClient {
             }
         }
 
-        Ok(Self {
-            encryption_key_provider,
-            software_update_provider,
-            system_info_remote_managers,
-        })
+        system_info_remote_managers
+    }
+
+    /// Update remote manager API configurations from current config.
+    pub fn reload_system_info_remote_managers(&self, config: &Config) {
+        let new = Self::create_system_info_remote_managers(config, &self.client, &self.api_key);
+        match self.system_info_remote_managers.write() {
+            Ok(mut remote_managers) => *remote_managers = new,
+            Err(e) => *e.into_inner() = new,
+        }
     }
 
     pub fn encryption_key_provider_config(&self) -> Result<&Configuration, ApiError> {
@@ -152,11 +179,34 @@ impl ApiClient {
     pub fn system_info_remote_manager_config(
         &self,
         manager_name: &str,
-    ) -> Result<&Configuration, ApiError> {
-        self.system_info_remote_managers
+    ) -> Result<Configuration, ApiError> {
+        let remote_managers = match self.system_info_remote_managers.read() {
+            Ok(remote_managers) => remote_managers,
+            Err(e) => e.into_inner(),
+        };
+        remote_managers
             .get(manager_name)
+            .cloned()
             .ok_or(ApiError::ManagerApiUrlNotConfigured("system_info_remote_manager_config").into())
     }
+
+    /// Reboot coordination config can be changed when config is reloaded,
+    /// so the configuration is created for every request.
+    pub fn reboot_coordinator_config(
+        &self,
+        coordination: &RebootCoordinationConfig,
+    ) -> Configuration {
+        Configuration {
+            base_path: coordination
+                .manager_base_url
+                .as_str()
+                .trim_end_matches('/')
+                .to_string(),
+            client: self.client.clone(),
+            api_key: Some(self.api_key.clone()),
+            ..Configuration::default()
+        }
+    }
 }
 
 pub struct ApiManager<'a> {
@@ -231,12 +281,46 @@ impl<'a> ApiManager<'a> {
         .change_context(ApiError::ApiRequest)
     }
 
-    pub async fn system_info(&self, remote_manager_name: &str) -> Result<SystemInfo, ApiError> {
+    pub async fn system_info(
+        &self,
+        remote_manager_name: &str,
+        sections: &SystemInfoSectionsQueryParam,
+    ) -> Result<SystemInfo, ApiError> {
         ManagerApi::system_info(
-            self.api_client
+            &self
+                .api_client
                 .system_info_remote_manager_config(remote_manager_name)?,
+            sections,
+        )
+        .await
+        .change_context(ApiError::ApiRequest)
+    }
+
+    pub async fn acquire_reboot_lease(&self) -> Result<RebootLease, ApiError> {
+        let coordination = self.reboot_coordination()?;
+        ManagerApi::acquire_reboot_lease(
+            &self.api_client.reboot_coordinator_config(&coordination),
+            &coordination.name,
         )
         .await
         .change_context(ApiError::ApiRequest)
     }
+
+    pub async fn release_reboot_lease(&self) -> Result<(), ApiError> {
+        let coordination = self.reboot_coordination()?;
+        ManagerApi::release_reboot_lease(
+            &self.api_client.reboot_coordinator_config(&coordination),
+            &coordination.name,
+        )
+        .await
+        .change_context(ApiError::ApiRequest)
+    }
+
+    fn reboot_coordination(&self) -> Result<RebootCoordinationConfig, ApiError> {
+        self.config
+            .reloadable()
+            .reboot_if_needed()
+            .and_then(|reboot| reboot.coordination.clone())
+            .ok_or(ApiError::ManagerApiUrlNotConfigured("reboot_coordinator_config").into())
+    }
 }
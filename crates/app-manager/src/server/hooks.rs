@@ -0,0 +1,153 @@
+//! Run pre-reboot and post-boot hooks
+
+use error_stack::{Report, Result, ResultExt};
+use manager_model::CommandStatus;
+use tracing::{info, warn};
+
+use super::{
+    info::SystemInfoGetter,
+    state::{HookResults, StateStorage},
+};
+use crate::config::Config;
+
+const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
+
+#[derive(thiserror::Error, Debug)]
+pub enum HookError {
+    #[error("Pre-reboot hook failed: {0}")]
+    PreRebootHookFailed(String),
+    #[error("Reading boot ID failed")]
+    BootIdReadingFailed,
+}
+
+/// Hooks are run in the configured order. Running stops at the first
+/// failed hook which aborts the reboot. Outputs are saved to the state.
+pub async fn run_pre_reboot_hooks(config: &Config, state: &StateStorage) -> Result<(), HookError> {
+    let Some(hooks) = config.hooks().filter(|hooks| !hooks.pre_reboot.is_empty()) else {
+        return Ok(());
+    };
+
+    let mut outputs = vec![];
+    let mut result = Ok(());
+    for hook in &hooks.pre_reboot {
+        let name = &hook.command.name;
+        info!("Running pre-reboot hook {}", name);
+        let output = SystemInfoGetter::run_custom_command(&hook.command).await;
+        let failed = output.status != CommandStatus::Success;
+        outputs.push(output);
+        if failed && hook.abort_on_failure() {
+            result = Err(Report::new(HookError::PreRebootHookFailed(name.clone())));
+            break;
+        } else if failed {
+            warn!("Pre-reboot hook {} failed. Continuing reboot", name);
+        }
+    }
+
+    let results = HookResults::new(outputs);
+    state
+        .modify(|s| s.persistent.last_pre_reboot_hooks = Some(results))
+        .await;
+    result
+}
+
+/// Kernel generates a new boot ID for every boot.
+pub async fn current_boot_id() -> Result<String, HookError> {
+    let boot_id = tokio::fs::read_to_string(BOOT_ID_PATH)
+        .await
+        .change_context(HookError::BootIdReadingFailed)?;
+    Ok(boot_id.trim().to_string())
+}
+
+/// Hooks are run only once per boot, so manager restarts do not run
+/// them again. The boot ID is saved to the state also when there are
+/// no hooks, so enabling hooks does not run them before the next boot.
+pub async fn run_post_boot_hooks_once(config: &Config, state: &StateStorage, boot_id: String) {
+    let previous_boot_id = state.get(|s| s.persistent.boot_id.clone()).await;
+    if previous_boot_id.as_ref() == Some(&boot_id) {
+        info!("Post-boot hooks are already handled for the current boot");
+        return;
+    }
+
+    run_post_boot_hooks(config, state).await;
+    state.modify(|s| s.persistent.boot_id = Some(boot_id)).await;
+}
+
+/// All hooks are run even if some of them fail. Outputs are saved to
+/// the state.
+async fn run_post_boot_hooks(config: &Config, state: &StateStorage) {
+    let Some(hooks) = config.hooks().filter(|hooks| !hooks.post_boot.is_empty()) else {
+        return;
+    };
+
+    let mut outputs = vec![];
+    for command in &hooks.post_boot {
+        info!("Running post-boot hook {}", command.name);
+        let output = SystemInfoGetter::run_custom_command(command).await;
+        if output.status != CommandStatus::Success {
+            warn!("Post-boot hook {} failed", command.name);
+        }
+        outputs.push(output);
+    }
+
+    let results = HookResults::new(outputs);
+    state
+        .modify(|s| s.persistent.last_post_boot_hooks = Some(results))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_test_config;
+
+    const HOOKS: &str = r#"
+[[hooks.post_boot]]
+name = "true"
+command = "true"
+"#;
+
+    async fn hook_results(state: &StateStorage) -> Option<HookResults> {
+        state
+            .get(|s| s.persistent.last_post_boot_hooks.clone())
+            .await
+    }
+
+    #[tokio::test]
+    async fn post_boot_hooks_run_once_per_boot() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), HOOKS);
+        let state = StateStorage::new(&config);
+
+        run_post_boot_hooks_once(&config, &state, "boot1".to_string()).await;
+        let first = hook_results(&state).await.unwrap();
+        assert_eq!(first.outputs.len(), 1);
+
+        state
+            .modify(|s| s.persistent.last_post_boot_hooks = None)
+            .await;
+        run_post_boot_hooks_once(&config, &state, "boot1".to_string()).await;
+        assert_eq!(hook_results(&state).await, None);
+
+        run_post_boot_hooks_once(&config, &state, "boot2".to_string()).await;
+        assert!(hook_results(&state).await.is_some());
+        assert_eq!(
+            state.get(|s| s.persistent.boot_id.clone()).await.as_deref(),
+            Some("boot2")
+        );
+    }
+
+    #[tokio::test]
+    async fn boot_id_is_saved_without_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), "");
+        let state = StateStorage::new(&config);
+
+        run_post_boot_hooks_once(&config, &state, "boot1".to_string()).await;
+
+        assert_eq!(hook_results(&state).await, None);
+        assert_eq!(
+            state.get(|s| s.persistent.boot_id.clone()).await.as_deref(),
+            Some("boot1")
+        );
+    }
+}
@@ -1,13 +1,32 @@
 //! Get system info
 
-use std::process::ExitStatus;
-
-use error_stack::{Result, ResultExt};
-use manager_model::{CommandOutput, SystemInfo, SystemInfoList};
+use std::{
+    process::ExitStatus,
+    time::{Duration, Instant},
+};
+
+use manager_model::{
+    CommandOutput, CommandStatus, SystemInfo, SystemInfoList, SystemInfoSection,
+    SystemInfoSectionsQueryParam,
+};
 use tokio::process::Command;
+use tracing::error;
 
+use self::system_metrics::SystemMetricsReader;
 use super::client::ApiManager;
-use crate::config::Config;
+use crate::config::{
+    file::{CustomCommand, LogService},
+    Config,
+};
+
+pub mod journal;
+pub mod system_metrics;
+
+/// System info name if hostname command fails.
+const UNKNOWN_HOSTNAME: &str = "unknown";
+
+/// Timeout for built-in commands.
+const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
 
 #[derive(thiserror::Error, Debug)]
 pub enum SystemInfoError {
@@ -34,131 +53,281 @@ pub enum SystemInfoError {
 
     #[error("Api request failed")]
     ApiRequest,
+
+    #[error("Reading /proc file failed")]
+    ProcReadFailed,
+
+    #[error("Log service is not configured")]
+    UnknownLogService,
 }
 
 pub struct SystemInfoGetter;
 
 impl SystemInfoGetter {
+    /// Remote managers are queried concurrently. Errors are reported
+    /// using SystemInfo::error, so that one unavailable remote manager does
+    /// not prevent getting other infos. Infos are in config file order.
     pub async fn system_info_all(
         config: &Config,
         api: &ApiManager<'_>,
-    ) -> Result<SystemInfoList, SystemInfoError> {
-        let system_info = Self::system_info(config).await?;
+        sections: &[SystemInfoSection],
+    ) -> SystemInfoList {
+        let reloadable = config.reloadable();
+        let info_config = reloadable.system_info();
+        let timeout = info_config
+            .map(|info_config| info_config.remote_timeout())
+            .unwrap_or_default();
+        let remote_infos = futures::future::join_all(
+            info_config
+                .and_then(|info_config| info_config.remote_managers.as_ref())
+                .into_iter()
+                .flatten()
+                .map(|manager| Self::remote_system_info(api, &manager.name, timeout, sections)),
+        );
+
+        let (system_info, remote_infos) =
+            tokio::join!(Self::system_info(config, sections), remote_infos);
         let mut system_infos = vec![system_info];
+        system_infos.extend(remote_infos);
+
+        SystemInfoList { info: system_infos }
+    }
 
-        if let Some(info_config) = config.system_info() {
-            for service in info_config.remote_managers.iter().flatten() {
-                match api.system_info(&service.name).await {
-                    Ok(info) => {
-                        let info = SystemInfo {
-                            name: format!(
-                                "Remote manager {}, remote name: {}",
-                                service.name, info.name
-                            ),
-                            info: info.info,
-                        };
-                        system_infos.push(info);
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to get system info from {}: {:?}", service.name, e);
-                        let _error = e.to_string();
-                    }
+    async fn remote_system_info(
+        api: &ApiManager<'_>,
+        remote_manager_name: &str,
+        timeout: Duration,
+        sections: &[SystemInfoSection],
+    ) -> SystemInfo {
+        let start = Instant::now();
+        let sections = SystemInfoSectionsQueryParam::new(sections);
+        let result =
+            tokio::time::timeout(timeout, api.system_info(remote_manager_name, &sections)).await;
+        let duration_millis = duration_millis(start);
+
+        match result {
+            Ok(Ok(info)) => SystemInfo {
+                name: format!(
+                    "Remote manager {}, remote name: {}",
+                    remote_manager_name, info.name
+                ),
+                info: info.info,
+                metrics: info.metrics,
+                error: info.error,
+                duration_millis,
+            },
+            Ok(Err(e)) => {
+                error!(
+                    "Failed to get system info from {}: {:?}",
+                    remote_manager_name, e
+                );
+                SystemInfo {
+                    name: format!("Remote manager {}", remote_manager_name),
+                    error: Some(format!("{:#}", e)),
+                    duration_millis,
+                    ..Default::default()
+                }
+            }
+            Err(_) => {
+                error!("Getting system info from {} timed out", remote_manager_name);
+                SystemInfo {
+                    name: format!("Remote manager {}", remote_manager_name),
+                    error: Some(format!(
+                        "Request timed out after {} seconds",
+                        timeout.as_secs()
+                    )),
+                    duration_millis,
+                    ..Default::default()
                 }
             }
         }
-
-        Ok(SystemInfoList { info: system_infos })
     }
 
-    pub async fn system_info(config: &Config) -> Result<SystemInfo, SystemInfoError> {
-        let df = Self::run_df().await?;
-        let df_inodes = Self::run_df_inodes().await?;
-        let uptime = Self::run_uptime().await?;
-        let free = Self::run_free().await?;
-        let print_logs = Self::run_print_logs(config).await?;
-
-        let whoami = Self::run_whoami().await?;
-        let username = whoami.output.trim().to_string();
-        let top = Self::run_top(&username).await?;
+    /// Failed commands are included in the info with failure status.
+    /// Only the selected sections are included.
+    pub async fn system_info(config: &Config, sections: &[SystemInfoSection]) -> SystemInfo {
+        let start = Instant::now();
+        let reloadable = config.reloadable();
+        let info_config = reloadable.system_info();
+        let mut commands = vec![];
+
+        if sections.contains(&SystemInfoSection::Commands) {
+            commands.push(Self::run_df().await);
+            commands.push(Self::run_df_inodes().await);
+            commands.push(Self::run_uptime().await);
+            commands.push(Self::run_free().await);
+
+            let whoami = Self::run_whoami().await;
+            let username = whoami.output.trim().to_string();
+            commands.push(Self::run_top(&username).await);
+        }
 
-        let mut commands = vec![df, df_inodes, uptime, free, top, print_logs];
+        if sections.contains(&SystemInfoSection::CustomCommands) {
+            for command in info_config
+                .and_then(|info_config| info_config.commands.as_ref())
+                .into_iter()
+                .flatten()
+            {
+                commands.push(Self::run_custom_command(command).await);
+            }
+        }
 
-        if let Some(info_config) = config.system_info() {
-            for service in info_config.log_services.iter() {
-                let journalctl = Self::run_journalctl(service).await?;
-                commands.push(journalctl);
+        if sections.contains(&SystemInfoSection::Logs) {
+            commands.push(Self::run_print_logs(config).await);
+            for service in info_config
+                .map(|info_config| info_config.log_services.as_slice())
+                .unwrap_or_default()
+            {
+                commands.push(Self::run_journalctl(service).await);
             }
         }
 
-        let hostname = Self::run_hostname().await?;
-        Ok(SystemInfo {
-            name: hostname.output.trim().to_string(),
+        let metrics = if sections.contains(&SystemInfoSection::Metrics) {
+            match SystemMetricsReader::read().await {
+                Ok(metrics) => Some(metrics),
+                Err(e) => {
+                    error!("Reading system metrics failed. Error: {:?}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let hostname = Self::run_hostname().await;
+        let name = match hostname.status {
+            CommandStatus::Success => hostname.output.trim().to_string(),
+            CommandStatus::Failed | CommandStatus::StartFailed | CommandStatus::Timeout => {
+                UNKNOWN_HOSTNAME.to_string()
+            }
+        };
+
+        SystemInfo {
+            name,
             info: commands,
-        })
+            metrics,
+            error: None,
+            duration_millis: duration_millis(start),
+        }
     }
 
-    async fn run_df() -> Result<CommandOutput, SystemInfoError> {
+    async fn run_df() -> CommandOutput {
         Self::run_cmd_with_args("df", &["-h"]).await
     }
 
-    async fn run_df_inodes() -> Result<CommandOutput, SystemInfoError> {
+    async fn run_df_inodes() -> CommandOutput {
         Self::run_cmd_with_args("df", &["-hi"]).await
     }
 
-    async fn run_uptime() -> Result<CommandOutput, SystemInfoError> {
+    async fn run_uptime() -> CommandOutput {
         Self::run_cmd_with_args("uptime", &[]).await
     }
 
-    async fn run_hostname() -> Result<CommandOutput, SystemInfoError> {
+    async fn run_hostname() -> CommandOutput {
         Self::run_cmd_with_args("hostname", &[]).await
     }
 
-    async fn run_whoami() -> Result<CommandOutput, SystemInfoError> {
+    async fn run_whoami() -> CommandOutput {
         Self::run_cmd_with_args("whoami", &[]).await
     }
 
-    async fn run_top(username: &str) -> Result<CommandOutput, SystemInfoError> {
+    async fn run_top(username: &str) -> CommandOutput {
         Self::run_cmd_with_args("top", &["-bn", "1", "-u", username]).await
     }
 
-    async fn run_free() -> Result<CommandOutput, SystemInfoError> {
+    async fn run_free() -> CommandOutput {
         Self::run_cmd_with_args("free", &["-h"]).await
     }
 
-    async fn run_journalctl(service: &str) -> Result<CommandOutput, SystemInfoError> {
-        Self::run_cmd_with_args("journalctl", &["--no-pager", "-n", "20", "-u", service]).await
+    async fn run_journalctl(service: &LogService) -> CommandOutput {
+        let lines = service.lines().to_string();
+        let mut args = vec!["--no-pager", "-n", &lines, "-u", service.name()];
+        if let Some(options) = service.options() {
+            if let Some(since) = &options.since {
+                args.extend(["--since", since]);
+            }
+            if let Some(until) = &options.until {
+                args.extend(["--until", until]);
+            }
+            if let Some(priority) = &options.priority {
+                args.extend(["--priority", priority]);
+            }
+        }
+        Self::run_cmd_with_args("journalctl", &args).await
+    }
+
+    /// Command from config file. Output name is the configured name.
+    pub async fn run_custom_command(command: &CustomCommand) -> CommandOutput {
+        let mut args: Vec<&str> = command
+            .args
+            .iter()
+            .flatten()
+            .map(|arg| arg.as_str())
+            .collect();
+        let cmd = if command.sudo() {
+            args.insert(0, &command.command);
+            "sudo"
+        } else {
+            &command.command
+        };
+        Self::run_cmd(command.name.clone(), cmd, &args, command.timeout()).await
     }
 
     /// Run print-logs.sh script which prints some logs requiring sudo.
-    async fn run_print_logs(config: &Config) -> Result<CommandOutput, SystemInfoError> {
+    async fn run_print_logs(config: &Config) -> CommandOutput {
         let script = config.script_locations().print_logs();
-        let script_str = script.to_str().ok_or(SystemInfoError::InvalidInput)?;
-        Self::run_cmd_with_args("sudo", &[script_str]).await
+        Self::run_cmd_with_args("sudo", &[&script.to_string_lossy()]).await
     }
 
-    async fn run_cmd_with_args(cmd: &str, args: &[&str]) -> Result<CommandOutput, SystemInfoError> {
-        let output = Command::new(cmd)
-            .args(args)
-            .output()
-            .await
-            .change_context(SystemInfoError::ProcessWaitFailed)?;
-
-        if !output.status.success() {
-            tracing::error!(
-                "{} {} failed with status: {:?}",
-                cmd,
-                args.join(" "),
-                output.status
-            );
-            return Err(SystemInfoError::CommandFailed(output.status).into());
-        }
-
-        let output =
-            String::from_utf8(output.stdout).change_context(SystemInfoError::InvalidOutput)?;
+    async fn run_cmd_with_args(cmd: &str, args: &[&str]) -> CommandOutput {
+        let name = format!("{} {}", cmd, args.join(" "));
+        Self::run_cmd(name, cmd, args, DEFAULT_COMMAND_TIMEOUT).await
+    }
 
-        Ok(CommandOutput {
-            name: format!("{} {}", cmd, args.join(" ")),
-            output,
-        })
+    /// The process is killed if the timeout expires.
+    async fn run_cmd(name: String, cmd: &str, args: &[&str], timeout: Duration) -> CommandOutput {
+        let output = Command::new(cmd).args(args).kill_on_drop(true).output();
+        let output = match tokio::time::timeout(timeout, output).await {
+            Ok(Ok(output)) => output,
+            Err(_) => {
+                error!("{} timed out", name);
+                return CommandOutput {
+                    name,
+                    output: String::new(),
+                    status: CommandStatus::Timeout,
+                    exit_code: None,
+                    stderr: format!("Command timed out after {} seconds", timeout.as_secs()),
+                };
+            }
+            Ok(Err(e)) => {
+                error!("{} start failed. Error: {}", name, e);
+                return CommandOutput {
+                    name,
+                    output: String::new(),
+                    status: CommandStatus::StartFailed,
+                    exit_code: None,
+                    stderr: e.to_string(),
+                };
+            }
+        };
+
+        let status = if output.status.success() {
+            CommandStatus::Success
+        } else {
+            error!("{} failed with status: {:?}", name, output.status);
+            CommandStatus::Failed
+        };
+
+        CommandOutput {
+            name,
+            output: String::from_utf8_lossy(&output.stdout).to_string(),
+            status,
+            exit_code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        }
     }
 }
+
+fn duration_millis(start: Instant) -> u64 {
+    start.elapsed().as_millis().try_into().unwrap_or(u64::MAX)
+}
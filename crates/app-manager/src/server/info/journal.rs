@@ -0,0 +1,96 @@
+//! Stream journal logs of configured services
+
+use std::process::Stdio;
+
+use error_stack::{Result, ResultExt};
+use futures::Stream;
+use manager_model::LogsQueryParam;
+use tokio::{
+    io::{AsyncBufReadExt, BufReader, Lines},
+    process::{Child, ChildStdout, Command},
+};
+
+use super::SystemInfoError;
+use crate::{
+    config::{check::is_valid_journal_priority, Config},
+    utils::ContextExt,
+};
+
+/// Keep the child process with the output, so that the process is killed
+/// when the stream is dropped.
+type JournalOutput = (Child, Lines<BufReader<ChildStdout>>);
+
+pub struct JournalReader;
+
+impl JournalReader {
+    /// Start journalctl for a service from system info log_services config.
+    pub fn read(
+        config: &Config,
+        query: LogsQueryParam,
+    ) -> Result<impl Stream<Item = std::io::Result<String>>, SystemInfoError> {
+        let reloadable = config.reloadable();
+        let service = reloadable
+            .system_info()
+            .and_then(|info_config| {
+                info_config
+                    .log_services
+                    .iter()
+                    .find(|service| service.name() == query.service)
+            })
+            .ok_or_else(|| SystemInfoError::UnknownLogService.report())?;
+        let options = service.options();
+
+        let priority = query
+            .priority
+            .as_ref()
+            .or_else(|| options.and_then(|options| options.priority.as_ref()));
+        let since = query
+            .since
+            .as_ref()
+            .or_else(|| options.and_then(|options| options.since.as_ref()));
+
+        let mut command = Command::new("journalctl");
+        command
+            .arg("--no-pager")
+            .args(["-n", &service.lines().to_string()])
+            .args(["-u", service.name()]);
+        if let Some(priority) = priority {
+            if !is_valid_journal_priority(priority) {
+                return Err(SystemInfoError::InvalidInput.report());
+            }
+            command.args(["--priority", priority]);
+        }
+        if let Some(since) = since {
+            command.args(["--since", since]);
+        }
+        if query.follow {
+            command.arg("--follow");
+        }
+
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .change_context(SystemInfoError::ProcessStartFailed)?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| SystemInfoError::ProcessStartFailed.report())?;
+
+        let state = Some((child, BufReader::new(stdout).lines()));
+        Ok(futures::stream::unfold(state, Self::next_line))
+    }
+
+    /// Stream ends after the first error.
+    async fn next_line(
+        state: Option<JournalOutput>,
+    ) -> Option<(std::io::Result<String>, Option<JournalOutput>)> {
+        let (child, mut lines) = state?;
+        match lines.next_line().await {
+            Ok(Some(line)) => Some((Ok(line + "\n"), Some((child, lines)))),
+            Ok(None) => None,
+            Err(e) => Some((Err(e), None)),
+        }
+    }
+}
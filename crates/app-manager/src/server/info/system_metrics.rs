@@ -0,0 +1,282 @@
+//! Read system metrics from /proc and statvfs
+
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use error_stack::{Report, Result, ResultExt};
+use manager_model::{DiskUsage, LoadAverage, MemoryUsage, ProcessInfo, SystemMetrics};
+use nix::{
+    sys::statvfs::statvfs,
+    unistd::{sysconf, SysconfVar},
+};
+
+use super::SystemInfoError;
+
+/// Time between two process CPU time samples.
+const CPU_SAMPLE_DURATION: Duration = Duration::from_millis(500);
+
+const TOP_PROCESSES_COUNT: usize = 10;
+
+pub struct SystemMetricsReader;
+
+impl SystemMetricsReader {
+    pub async fn read() -> Result<SystemMetrics, SystemInfoError> {
+        let cpu_count = Self::cpu_count()?;
+        let top_processes = Self::top_processes(cpu_count).await?;
+
+        Ok(SystemMetrics {
+            disks: Self::disks()?,
+            memory: Self::memory()?,
+            load_average: Self::load_average()?,
+            uptime_seconds: Self::uptime_seconds()?,
+            cpu_count,
+            top_processes,
+        })
+    }
+
+    fn disks() -> Result<Vec<DiskUsage>, SystemInfoError> {
+        let mounts = read_proc_file("/proc/mounts")?;
+        let mut disks: Vec<DiskUsage> = vec![];
+
+        for line in mounts.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(file_system), Some(mount_point)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let mount_point = unescape_mount_path(mount_point);
+            if disks.iter().any(|d| d.mount_point == mount_point) {
+                continue;
+            }
+
+            // Some mount points are not accessible without root.
+            let Ok(stat) = statvfs(Path::new(&mount_point)) else {
+                continue;
+            };
+            let block_size = stat.fragment_size();
+            let total_blocks = stat.blocks();
+            // Skip pseudo file systems like df does.
+            if total_blocks == 0 {
+                continue;
+            }
+
+            let total_inodes = stat.files();
+            disks.push(DiskUsage {
+                mount_point,
+                file_system: file_system.to_string(),
+                total_bytes: total_blocks * block_size,
+                used_bytes: (total_blocks - stat.blocks_free()) * block_size,
+                available_bytes: stat.blocks_available() * block_size,
+                total_inodes,
+                used_inodes: total_inodes.saturating_sub(stat.files_free()),
+                available_inodes: stat.files_available(),
+            });
+        }
+
+        Ok(disks)
+    }
+
+    fn memory() -> Result<MemoryUsage, SystemInfoError> {
+        let meminfo = read_proc_file("/proc/meminfo")?;
+        let values: HashMap<&str, u64> = meminfo
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                let kilobytes = value.trim().trim_end_matches("kB").trim().parse().ok()?;
+                Some((name, kilobytes))
+            })
+            .collect();
+        let bytes = |name: &str| -> Result<u64, SystemInfoError> {
+            values
+                .get(name)
+                .map(|kilobytes| kilobytes * 1024)
+                .ok_or_else(|| Report::new(SystemInfoError::InvalidOutput))
+                .attach_printable(format!("/proc/meminfo: {} is missing", name))
+        };
+
+        let total_bytes = bytes("MemTotal")?;
+        let available_bytes = bytes("MemAvailable")?;
+        let swap_total_bytes = bytes("SwapTotal")?;
+        Ok(MemoryUsage {
+            total_bytes,
+            used_bytes: total_bytes.saturating_sub(available_bytes),
+            available_bytes,
+            swap_total_bytes,
+            swap_used_bytes: swap_total_bytes.saturating_sub(bytes("SwapFree")?),
+        })
+    }
+
+    fn load_average() -> Result<LoadAverage, SystemInfoError> {
+        let loadavg = read_proc_file("/proc/loadavg")?;
+        let values = loadavg
+            .split_whitespace()
+            .take(3)
+            .map(|value| value.parse::<f64>())
+            .collect::<std::result::Result<Vec<f64>, _>>()
+            .change_context(SystemInfoError::InvalidOutput)?;
+
+        match values.as_slice() {
+            [one_minute, five_minutes, fifteen_minutes] => Ok(LoadAverage {
+                one_minute: *one_minute,
+                five_minutes: *five_minutes,
+                fifteen_minutes: *fifteen_minutes,
+            }),
+            _ => Err(Report::new(SystemInfoError::InvalidOutput))
+                .attach_printable(format!("/proc/loadavg: {}", loadavg)),
+        }
+    }
+
+    fn uptime_seconds() -> Result<u64, SystemInfoError> {
+        let uptime = read_proc_file("/proc/uptime")?;
+        let seconds = uptime
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .parse::<f64>()
+            .change_context(SystemInfoError::InvalidOutput)
+            .attach_printable(format!("/proc/uptime: {}", uptime))?;
+        Ok(seconds as u64)
+    }
+
+    fn cpu_count() -> Result<u32, SystemInfoError> {
+        let stat = read_proc_file("/proc/stat")?;
+        let count = stat
+            .lines()
+            .filter(|line| {
+                line.strip_prefix("cpu")
+                    .is_some_and(|cpu| cpu.starts_with(|c: char| c.is_ascii_digit()))
+            })
+            .count();
+        Ok(count.try_into().unwrap_or(u32::MAX))
+    }
+
+    /// Total CPU time of all CPUs in clock ticks.
+    fn total_cpu_time() -> Result<u64, SystemInfoError> {
+        let stat = read_proc_file("/proc/stat")?;
+        let cpu_line = stat
+            .lines()
+            .find(|line| line.starts_with("cpu "))
+            .ok_or_else(|| Report::new(SystemInfoError::InvalidOutput))
+            .attach_printable("/proc/stat: cpu line is missing")?;
+        Ok(cpu_line
+            .split_whitespace()
+            .skip(1)
+            .filter_map(|value| value.parse::<u64>().ok())
+            .sum())
+    }
+
+    async fn top_processes(cpu_count: u32) -> Result<Vec<ProcessInfo>, SystemInfoError> {
+        let page_size = sysconf(SysconfVar::PAGE_SIZE)
+            .change_context(SystemInfoError::InvalidOutput)?
+            .and_then(|size| u64::try_from(size).ok())
+            .unwrap_or(4096);
+
+        let first_total = Self::total_cpu_time()?;
+        let first = Self::process_samples(page_size)?;
+        tokio::time::sleep(CPU_SAMPLE_DURATION).await;
+        let second_total = Self::total_cpu_time()?;
+        let second = Self::process_samples(page_size)?;
+
+        let total_delta = second_total.saturating_sub(first_total).max(1) as f64;
+        let mut processes: Vec<ProcessInfo> = second
+            .into_iter()
+            .map(|(pid, sample)| {
+                let previous_cpu_time = first
+                    .get(&pid)
+                    .map(|previous| previous.cpu_time)
+                    .unwrap_or(sample.cpu_time);
+                let cpu_delta = sample.cpu_time.saturating_sub(previous_cpu_time) as f64;
+                ProcessInfo {
+                    pid,
+                    name: sample.name,
+                    cpu_percent: cpu_delta / total_delta * f64::from(cpu_count) * 100.0,
+                    memory_bytes: sample.memory_bytes,
+                }
+            })
+            .collect();
+
+        processes.sort_by(|a, b| {
+            b.cpu_percent
+                .total_cmp(&a.cpu_percent)
+                .then(b.memory_bytes.cmp(&a.memory_bytes))
+        });
+        processes.truncate(TOP_PROCESSES_COUNT);
+        Ok(processes)
+    }
+
+    fn process_samples(page_size: u64) -> Result<HashMap<u32, ProcessSample>, SystemInfoError> {
+        let mut samples = HashMap::new();
+        let dir = std::fs::read_dir("/proc").change_context(SystemInfoError::ProcReadFailed)?;
+
+        for entry in dir.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|pid| pid.parse().ok()) else {
+                continue;
+            };
+            // Process might quit while reading.
+            let Ok(stat) = std::fs::read_to_string(entry.path().join("stat")) else {
+                continue;
+            };
+            if let Some(sample) = ProcessSample::parse(&stat, page_size) {
+                samples.insert(pid, sample);
+            }
+        }
+
+        Ok(samples)
+    }
+}
+
+struct ProcessSample {
+    name: String,
+    /// User and system CPU time in clock ticks.
+    cpu_time: u64,
+    memory_bytes: u64,
+}
+
+impl ProcessSample {
+    /// Parse /proc/[pid]/stat
+    fn parse(stat: &str, page_size: u64) -> Option<Self> {
+        // Process name is inside parentheses and it can contain spaces.
+        let name_start = stat.find('(')?;
+        let name_end = stat.rfind(')')?;
+        let name = stat.get(name_start + 1..name_end)?.to_string();
+        let fields: Vec<&str> = stat.get(name_end + 1..)?.split_whitespace().collect();
+
+        let utime: u64 = fields.get(11)?.parse().ok()?;
+        let stime: u64 = fields.get(12)?.parse().ok()?;
+        let rss_pages: u64 = fields.get(21)?.parse().ok()?;
+
+        Some(Self {
+            name,
+            cpu_time: utime + stime,
+            memory_bytes: rss_pages * page_size,
+        })
+    }
+}
+
+fn read_proc_file(path: &str) -> Result<String, SystemInfoError> {
+    std::fs::read_to_string(path)
+        .change_context(SystemInfoError::ProcReadFailed)
+        .attach_printable(path.to_string())
+}
+
+/// Paths in /proc/mounts have some characters escaped using octal
+/// escape sequences. For example space is "\040".
+fn unescape_mount_path(path: &str) -> String {
+    let mut bytes = vec![];
+    let mut chars = path.as_bytes().iter().peekable();
+    while let Some(&c) = chars.next() {
+        if c == b'\\' {
+            let digits: Vec<u8> = chars.clone().take(3).copied().collect();
+            if digits.len() == 3 && digits.iter().all(|d| (b'0'..=b'7').contains(d)) {
+                let value = digits
+                    .iter()
+                    .fold(0u32, |value, d| value * 8 + u32::from(d - b'0'));
+                if let Ok(value) = u8::try_from(value) {
+                    bytes.push(value);
+                    chars.nth(2);
+                    continue;
+                }
+            }
+        }
+        bytes.push(c);
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
@@ -0,0 +1,118 @@
+//! Logging setup
+
+use tracing::error;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    fmt::MakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
+
+use crate::config::{
+    file::{LogFormat, LogRotation, LoggingConfig},
+    Config,
+};
+
+const LOG_FILE_NAME_PREFIX: &str = "app-manager";
+const LOG_FILE_NAME_SUFFIX: &str = "log";
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Keep this alive until the manager quits. Otherwise some log messages
+/// might not be written to the log file.
+pub struct LoggingGuard {
+    _file_writer: Option<WorkerGuard>,
+}
+
+/// Initialize global logger. Errors are logged after the logger is
+/// initialized.
+pub fn init_logging(config: &Config) -> LoggingGuard {
+    let default_logging = LoggingConfig::default();
+    let logging = config.logging().unwrap_or(&default_logging);
+    let mut errors = vec![];
+
+    let mut layers: Vec<BoxedLayer> = vec![fmt_layer(
+        logging.format(),
+        config.log_timestamp(),
+        true,
+        std::io::stdout,
+    )];
+
+    let mut file_writer = None;
+    if let Some(file) = &logging.file {
+        let dir = file.dir(config.storage_dir());
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            errors.push(format!(
+                "Log directory {} creation failed. Error: {}",
+                dir.display(),
+                e
+            ));
+        }
+        let appender = RollingFileAppender::builder()
+            .rotation(match file.rotation() {
+                LogRotation::Minutely => Rotation::MINUTELY,
+                LogRotation::Hourly => Rotation::HOURLY,
+                LogRotation::Daily => Rotation::DAILY,
+                LogRotation::Never => Rotation::NEVER,
+            })
+            .filename_prefix(LOG_FILE_NAME_PREFIX)
+            .filename_suffix(LOG_FILE_NAME_SUFFIX)
+            .max_log_files(file.max_files())
+            .build(dir);
+        match appender {
+            Ok(appender) => {
+                let (writer, guard) = tracing_appender::non_blocking(appender);
+                file_writer = Some(guard);
+                layers.push(fmt_layer(logging.format(), true, false, writer));
+            }
+            Err(e) => errors.push(format!("Log file creation failed. Error: {}", e)),
+        }
+    }
+
+    if logging.journald() {
+        match tracing_journald::layer() {
+            Ok(layer) => layers.push(layer.boxed()),
+            Err(e) => errors.push(format!("Connecting to journald failed. Error: {}", e)),
+        }
+    }
+
+    let filter = match (std::env::var(EnvFilter::DEFAULT_ENV), &logging.level) {
+        (Err(_), Some(level)) => match EnvFilter::try_new(level) {
+            Ok(filter) => filter,
+            Err(e) => {
+                errors.push(format!("Invalid log level {}. Error: {}", level, e));
+                EnvFilter::from_default_env()
+            }
+        },
+        _ => EnvFilter::from_default_env(),
+    };
+
+    tracing_subscriber::registry()
+        .with(layers)
+        .with(filter)
+        .init();
+
+    for e in errors {
+        error!("{}", e);
+    }
+
+    LoggingGuard {
+        _file_writer: file_writer,
+    }
+}
+
+fn fmt_layer<W>(format: LogFormat, timestamp: bool, ansi: bool, writer: W) -> BoxedLayer
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_ansi(ansi)
+        .with_writer(writer);
+    match (format, timestamp) {
+        (LogFormat::Text, true) => layer.boxed(),
+        (LogFormat::Text, false) => layer.without_time().boxed(),
+        (LogFormat::Json, true) => layer.json().boxed(),
+        (LogFormat::Json, false) => layer.json().without_time().boxed(),
+    }
+}
@@ -0,0 +1,241 @@
+//! Prometheus metrics
+
+use std::{
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use error_stack::{Result, ResultExt};
+use manager_model::SoftwareOptions;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use time::OffsetDateTime;
+
+use super::mount::MountMode;
+
+/// Build durations are usually minutes.
+const BUILD_DURATION_BUCKETS: &[f64] = &[
+    30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0,
+];
+
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+#[derive(thiserror::Error, Debug)]
+pub enum MetricsError {
+    #[error("Metrics encoding failed")]
+    EncodingFailed,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BackendAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+impl BackendAction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+        }
+    }
+}
+
+pub struct Metrics {
+    registry: Registry,
+    api_requests: IntCounterVec,
+    api_auth_failures: IntCounterVec,
+    api_locked: IntGauge,
+    build_duration: HistogramVec,
+    updates: IntCounterVec,
+    mount_mode: IntGaugeVec,
+    reboot_check_enabled: IntGauge,
+    reboot_check_seconds: IntGauge,
+    next_reboot_check: Mutex<Option<OffsetDateTime>>,
+    backend_control_failures: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry =
+            Registry::new_custom(Some("app_manager".to_string()), None).expect("Prefix is valid");
+
+        let api_requests = IntCounterVec::new(
+            Opts::new("api_requests_total", "API requests by route and status"),
+            &["route", "status"],
+        )
+        .expect("Metric is valid");
+        let api_auth_failures = IntCounterVec::new(
+            Opts::new("api_auth_failures_total", "API key authentication failures"),
+            &["reason"],
+        )
+        .expect("Metric is valid");
+        let api_locked = IntGauge::new("api_locked", "API is locked because of wrong API key")
+            .expect("Metric is valid");
+        let build_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "build_duration_seconds",
+                "Software build duration and result",
+            )
+            .buckets(BUILD_DURATION_BUCKETS.to_vec()),
+            &["software", "result"],
+        )
+        .expect("Metric is valid");
+        let updates = IntCounterVec::new(
+            Opts::new("software_updates_total", "Software update results"),
+            &["software", "result"],
+        )
+        .expect("Metric is valid");
+        let mount_mode = IntGaugeVec::new(
+            Opts::new(
+                "mount_mode",
+                "Current secure storage mount mode has value 1",
+            ),
+            &["mode"],
+        )
+        .expect("Metric is valid");
+        let reboot_check_enabled =
+            IntGauge::new("reboot_check_enabled", "Automatic reboot check is enabled")
+                .expect("Metric is valid");
+        let reboot_check_seconds = IntGauge::new(
+            "reboot_check_seconds_until",
+            "Time until next automatic reboot check",
+        )
+        .expect("Metric is valid");
+        let backend_control_failures = IntCounterVec::new(
+            Opts::new(
+                "backend_control_failures_total",
+                "Backend start and stop failures",
+            ),
+            &["action"],
+        )
+        .expect("Metric is valid");
+
+        registry
+            .register(Box::new(api_requests.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(api_auth_failures.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(api_locked.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(build_duration.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(updates.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(mount_mode.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(reboot_check_enabled.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(reboot_check_seconds.clone()))
+            .expect("Metric is not registered");
+        registry
+            .register(Box::new(backend_control_failures.clone()))
+            .expect("Metric is not registered");
+
+        Self {
+            registry,
+            api_requests,
+            api_auth_failures,
+            api_locked,
+            build_duration,
+            updates,
+            mount_mode,
+            reboot_check_enabled,
+            reboot_check_seconds,
+            next_reboot_check: Mutex::new(None),
+            backend_control_failures,
+        }
+    }
+
+    pub fn api_request(&self, route: &str, status: u16) {
+        self.api_requests
+            .with_label_values(&[route, &status.to_string()])
+            .inc();
+    }
+
+    pub fn api_auth_failure(&self, reason: &str) {
+        self.api_auth_failures.with_label_values(&[reason]).inc();
+    }
+
+    pub fn api_locked_changed(&self, locked: bool) {
+        self.api_locked.set(locked.into());
+    }
+
+    pub fn build_finished(&self, software: SoftwareOptions, duration: Duration, success: bool) {
+        self.build_duration
+            .with_label_values(&[software.to_str(), result_label(success)])
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn update_finished(&self, software: SoftwareOptions, success: bool) {
+        self.updates
+            .with_label_values(&[software.to_str(), result_label(success)])
+            .inc();
+    }
+
+    pub fn mount_mode_changed(&self, mode: MountMode) {
+        for m in MountMode::ALL {
+            self.mount_mode
+                .with_label_values(&[m.as_str()])
+                .set((*m == mode).into());
+        }
+    }
+
+    /// Set None if automatic reboot check is disabled.
+    pub fn next_reboot_check(&self, time: Option<OffsetDateTime>) {
+        if let Ok(mut next) = self.next_reboot_check.lock() {
+            *next = time;
+        }
+    }
+
+    pub fn backend_control_failed(&self, action: BackendAction) {
+        self.backend_control_failures
+            .with_label_values(&[action.as_str()])
+            .inc();
+    }
+
+    /// Metrics in Prometheus text format
+    pub fn encode(&self) -> Result<String, MetricsError> {
+        self.update_reboot_check_time();
+
+        let mut buffer = vec![];
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .change_context(MetricsError::EncodingFailed)?;
+        String::from_utf8(buffer).change_context(MetricsError::EncodingFailed)
+    }
+
+    fn update_reboot_check_time(&self) {
+        let next = self.next_reboot_check.lock().ok().and_then(|next| *next);
+        match next {
+            Some(next) => {
+                let seconds = (next - OffsetDateTime::now_utc()).whole_seconds().max(0);
+                self.reboot_check_enabled.set(1);
+                self.reboot_check_seconds.set(seconds);
+            }
+            None => {
+                self.reboot_check_enabled.set(0);
+                self.reboot_check_seconds.set(0);
+            }
+        }
+    }
+}
+
+fn result_label(success: bool) -> &'static str {
+    if success {
+        "success"
+    } else {
+        "failure"
+    }
+}
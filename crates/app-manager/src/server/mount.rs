@@ -11,7 +11,7 @@ use manager_model::DataEncryptionKey;
 use tokio::{io::AsyncWriteExt, process::Command};
 use tracing::{error, info, warn};
 
-use super::{app::AppState, state::StateStorage};
+use super::{app::AppState, metrics::METRICS, state::StateStorage};
 use crate::{
     api::GetApiManager,
     config::{file::SecureStorageConfig, Config},
@@ -44,6 +44,33 @@ pub enum MountMode {
     MountedWithUnknownKey,
 }
 
+impl MountMode {
+    pub const ALL: &'static [MountMode] = &[
+        Self::NotMounted,
+        Self::MountedWithRemoteKey,
+        Self::MountedWithLocalKey,
+        Self::MountedWithDefaultKey,
+        Self::MountedWithUnknownKey,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotMounted => "not_mounted",
+            Self::MountedWithRemoteKey => "remote_key",
+            Self::MountedWithLocalKey => "local_key",
+            Self::MountedWithDefaultKey => "default_key",
+            Self::MountedWithUnknownKey => "unknown_key",
+        }
+    }
+
+    pub fn from_str_value(value: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|mode| mode.as_str() == value)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MountState {
     mode: MountMode,
@@ -51,6 +78,7 @@ pub struct MountState {
 
 impl MountState {
     pub fn new() -> Self {
+        METRICS.mount_mode_changed(MountMode::NotMounted);
         Self {
             mode: MountMode::NotMounted,
         }
@@ -61,6 +89,7 @@ impl MountState {
     }
 
     fn set_mode(&mut self, mode: MountMode) {
+        METRICS.mount_mode_changed(mode);
         self.mode = mode;
     }
 }
@@ -86,18 +115,26 @@ impl MountManager {
         }
     }
 
+    /// The previous mode is used if secure storage is already mounted.
+    /// It is available when the previous manager process handed off to
+    /// an updated manager binary or when the state file contains it.
     pub async fn mount_if_needed(
         &self,
         storage_config: &SecureStorageConfig,
+        previous_mode: Option<MountMode>,
     ) -> Result<(), MountError> {
         if storage_config.availability_check_path.exists() {
             info!("Secure storage is already mounted");
-            self.state
-                .modify(|s| s.mount_state.set_mode(MountMode::MountedWithUnknownKey))
-                .await;
+            let mode = previous_mode
+                .filter(|mode| *mode != MountMode::NotMounted)
+                .unwrap_or(MountMode::MountedWithUnknownKey);
+            self.state.modify(|s| s.mount_state.set_mode(mode)).await;
             return Ok(());
         }
 
+        // Saved mode is from a previous mount.
+        self.clear_mount_mode().await;
+
         let key = self
             .app_state
             .api_manager()
@@ -113,7 +150,7 @@ impl MountManager {
                     warn!("Using local encryption key. This shouldn't be done in production!");
                     (
                         Some(DataEncryptionKey {
-                            key: text.to_string(),
+                            key: text.as_str().to_string(),
                         }),
                         MountMode::MountedWithLocalKey,
                     )
@@ -188,11 +225,16 @@ impl MountManager {
     ) -> Result<(), MountError> {
         if !storage_config.availability_check_path.exists() {
             info!("Secure storage is already unmounted");
+            self.clear_mount_mode().await;
             return Ok(());
         }
 
         info!("Unmounting secure storage");
 
+        // The state file can be in the secure storage, so the mode is
+        // cleared before unmounting.
+        let previous_mode = self.clear_mount_mode().await;
+
         // Run command.
         let c = Command::new("sudo")
             .arg(self.config.script_locations().close_encryption())
@@ -205,10 +247,25 @@ impl MountManager {
             Ok(())
         } else {
             error!("Unmounting failed.");
+            self.state
+                .modify(|s| s.mount_state.set_mode(previous_mode))
+                .await;
             Err(MountError::CommandFailed(c).report())
         }
     }
 
+    /// Returns the previous mode.
+    async fn clear_mount_mode(&self) -> MountMode {
+        self.state
+            .modify(|s| {
+                let previous_mode = s.mount_state.mode();
+                s.mount_state.set_mode(MountMode::NotMounted);
+                s.persistent.mount_mode = None;
+                previous_mode
+            })
+            .await
+    }
+
     async fn is_default_password(&self) -> Result<bool, MountError> {
         let c = Command::new("sudo")
             .arg(
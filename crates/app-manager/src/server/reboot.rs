@@ -1,22 +1,17 @@
 //! Handle automatic reboots
 
-use std::{
-    path::Path,
-    process::ExitStatus,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
-    time::Duration,
-};
+use std::{path::Path, process::ExitStatus, sync::Arc, time::Duration};
 
-use error_stack::{Result, ResultExt};
-use time::{OffsetDateTime, Time};
+use error_stack::{Report, Result, ResultExt};
+use manager_model::{RebootAttempt, RebootReason, RebootStatus};
+use time::OffsetDateTime;
 use tokio::{process::Command, sync::mpsc, task::JoinHandle, time::sleep};
 use tracing::{debug, info, warn};
 
 use super::{
     client::{ApiClient, ApiManager},
+    hooks,
+    metrics::METRICS,
     state::StateStorage,
     ServerQuitWatcher,
 };
@@ -25,16 +20,16 @@ use crate::{config::Config, server::mount::MountMode};
 /// If this file exists reboot system at some point. Works at least on Ubuntu.
 const REBOOT_REQUIRED_PATH: &str = "/var/run/reboot-required";
 
-pub static REBOOT_ON_NEXT_CHECK: AtomicBool = AtomicBool::new(false);
+/// Minimum time between reboot checks. Inside maintenance windows
+/// reboot is checked using this interval, so failed reboots are retried
+/// in the same window.
+const REBOOT_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
 
 #[derive(thiserror::Error, Debug)]
 pub enum RebootError {
     #[error("Reboot manager not available")]
     RebootManagerNotAvailable,
 
-    #[error("Time related error")]
-    TimeError,
-
     #[error("Config related error")]
     ConfigError,
 
@@ -52,6 +47,15 @@ pub enum RebootError {
 
     #[error("Getting encryption key failed")]
     GetKeyFailed,
+
+    #[error("Reboot lease request failed")]
+    RebootLeaseFailed,
+
+    #[error("Reboot lease not granted")]
+    RebootLeaseNotGranted,
+
+    #[error("Pre-reboot hook aborted reboot")]
+    PreRebootHookFailed,
 }
 
 #[derive(Debug)]
@@ -75,11 +79,18 @@ impl RebootManagerQuitHandle {
 #[derive(Debug)]
 pub enum RebootManagerMessage {
     RebootNow,
+    /// Recalculate the next reboot check time.
+    ConfigReloaded,
+    /// Manager startup is complete, so reboot checks can start. The
+    /// reboot lease is released if it was acquired before the previous
+    /// reboot.
+    StartupComplete,
 }
 
 #[derive(Debug, Clone)]
 pub struct RebootManagerHandle {
     sender: mpsc::Sender<RebootManagerMessage>,
+    state: Arc<StateStorage>,
 }
 
 impl RebootManagerHandle {
@@ -91,6 +102,66 @@ impl RebootManagerHandle {
 
         Ok(())
     }
+
+    pub async fn config_reloaded(&self) -> Result<(), RebootError> {
+        self.sender
+            .send(RebootManagerMessage::ConfigReloaded)
+            .await
+            .change_context(RebootError::RebootManagerNotAvailable)?;
+
+        Ok(())
+    }
+
+    /// Call this after secure storage is mounted, the state is loaded and
+    /// the backend is started. Reboot checks do not run before this and
+    /// the next server can reboot after this.
+    pub async fn startup_complete(&self) -> Result<(), RebootError> {
+        self.sender
+            .send(RebootManagerMessage::StartupComplete)
+            .await
+            .change_context(RebootError::RebootManagerNotAvailable)?;
+
+        Ok(())
+    }
+
+    /// Reboot at the next reboot check. Reason of an already pending
+    /// reboot is kept.
+    pub async fn schedule_reboot(&self, reason: RebootReason) {
+        self.state
+            .modify(|s| {
+                s.persistent.pending_reboot.get_or_insert(reason);
+            })
+            .await;
+    }
+
+    /// Returns false if there was no pending reboot. The reboot required
+    /// file is not removed, so the system is still rebooted if the file
+    /// exists.
+    pub async fn cancel_reboot(&self) -> bool {
+        self.state
+            .modify(|s| s.persistent.pending_reboot.take())
+            .await
+            .is_some()
+    }
+
+    pub async fn status(&self) -> RebootStatus {
+        let reboot_required_file = Path::new(REBOOT_REQUIRED_PATH).exists();
+        self.state
+            .get(|s| {
+                let reason = s.persistent.pending_reboot.or(if reboot_required_file {
+                    Some(RebootReason::RebootRequiredFile)
+                } else {
+                    None
+                });
+                RebootStatus {
+                    pending: reason.is_some(),
+                    reason,
+                    next_check_time: s.next_reboot_check.map(|time| time.unix_timestamp()),
+                    last_attempt: s.persistent.last_reboot_attempt.clone(),
+                }
+            })
+            .await
+    }
 }
 
 pub struct RebootManager {
@@ -98,6 +169,7 @@ pub struct RebootManager {
     api_client: Arc<ApiClient>,
     state: Arc<StateStorage>,
     receiver: mpsc::Receiver<RebootManagerMessage>,
+    startup_complete: bool,
 }
 
 impl RebootManager {
@@ -109,17 +181,21 @@ impl RebootManager {
     ) -> (RebootManagerQuitHandle, RebootManagerHandle) {
         let (sender, receiver) = mpsc::channel(1);
 
+        let handle = RebootManagerHandle {
+            sender,
+            state: state.clone(),
+        };
+
         let manager = Self {
             config,
             receiver,
             api_client,
             state,
+            startup_complete: false,
         };
 
         let task = tokio::spawn(manager.run(quit_notification));
 
-        let handle = RebootManagerHandle { sender };
-
         let quit_handle = RebootManagerQuitHandle {
             task,
             _sender: handle.sender.clone(),
@@ -131,17 +207,15 @@ impl RebootManager {
     pub async fn run(mut self, mut quit_notification: ServerQuitWatcher) {
         info!(
             "Automatic reboot status: {:?}",
-            self.config.reboot_if_needed().is_some()
+            self.config.reloadable().reboot_if_needed().is_some()
         );
 
-        let mut check_cooldown = false;
+        let mut previous_check = None;
 
         loop {
             tokio::select! {
-                _ = sleep(Duration::from_secs(120)), if check_cooldown => {
-                    check_cooldown = false;
-                }
-                result = Self::sleep_until_reboot_check(&self.config), if !check_cooldown => {
+                result = Self::sleep_until_reboot_check(&self.config, &self.state, previous_check), if self.startup_complete => {
+                    previous_check = Some(OffsetDateTime::now_utc());
                     match result {
                         Ok(()) => {
                             debug!("Sleep completed");
@@ -153,7 +227,6 @@ impl RebootManager {
                             warn!("Sleep until reboot check failed. Error: {:?}", e);
                         }
                     }
-                    check_cooldown = true;
                 }
                 message = self.receiver.recv() => {
                     match message {
@@ -173,7 +246,7 @@ impl RebootManager {
         }
     }
 
-    pub async fn handle_message(&self, message: RebootManagerMessage) {
+    pub async fn handle_message(&mut self, message: RebootManagerMessage) {
         match message {
             RebootManagerMessage::RebootNow => match self.run_reboot().await {
                 Ok(()) => {
@@ -183,6 +256,19 @@ impl RebootManager {
                     warn!("Reboot failed. Error: {:?}", e);
                 }
             },
+            RebootManagerMessage::ConfigReloaded => {
+                // The sleep future is recreated when the run loop continues.
+                info!(
+                    "Automatic reboot status: {:?}",
+                    self.config.reloadable().reboot_if_needed().is_some()
+                );
+            }
+            RebootManagerMessage::StartupComplete => {
+                self.startup_complete = true;
+                if self.state.get(|s| s.persistent.reboot_lease_held).await {
+                    self.release_reboot_lease().await;
+                }
+            }
         }
     }
 
@@ -191,12 +277,16 @@ impl RebootManager {
             info!("Reboot required file exists. Rebooting system");
             self.run_reboot_and_log_error().await;
             true
-        } else if REBOOT_ON_NEXT_CHECK.load(Ordering::Relaxed) {
+        } else if self
+            .state
+            .get(|s| s.persistent.pending_reboot.is_some())
+            .await
+        {
             info!("Reboot was requested at some point. Rebooting system");
             self.run_reboot_and_log_error().await;
             true
         } else {
-            info!("No reboot needed");
+            debug!("No reboot needed");
             false
         }
     }
@@ -212,20 +302,81 @@ impl RebootManager {
         }
     }
 
+    /// The pending reboot is cleared before rebooting, so that the system
+    /// is not rebooted again when the manager starts. It is restored if
+    /// the reboot fails.
     pub async fn run_reboot(&self) -> Result<(), RebootError> {
-        match self.state.get(|s| s.mount_state.mode()).await {
+        let pending_reboot = self
+            .state
+            .modify(|s| s.persistent.pending_reboot.take())
+            .await;
+
+        let result = self.reboot_system().await;
+
+        let attempt = RebootAttempt {
+            time: OffsetDateTime::now_utc().unix_timestamp(),
+            success: result.is_ok(),
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        };
+        self.state
+            .modify(|s| {
+                if result.is_err() && s.persistent.pending_reboot.is_none() {
+                    s.persistent.pending_reboot = pending_reboot;
+                }
+                s.persistent.last_reboot_attempt = Some(attempt);
+            })
+            .await;
+
+        result
+    }
+
+    /// If reboot coordination is enabled, a reboot lease is acquired
+    /// before running pre-reboot hooks. The lease is released when the
+    /// manager starts again or if the reboot does not happen.
+    async fn reboot_system(&self) -> Result<(), RebootError> {
+        let coordination = self
+            .config
+            .reloadable()
+            .reboot_if_needed()
+            .is_some_and(|reboot| reboot.coordination.is_some());
+
+        let check_key_provider = match self.state.get(|s| s.mount_state.mode()).await {
             MountMode::MountedWithRemoteKey => {
                 info!("Remote encryption key detected. Checking encryption key availability before rebooting");
-                self.api_manager()
-                    .get_encryption_key()
-                    .await
-                    .change_context(RebootError::GetKeyFailed)?;
-                info!("Remote encryption key is available");
+                true
             }
-            _ => (),
+            _ if coordination && self.config.secure_storage_config().is_some() => {
+                info!("Reboot coordination is enabled. Checking encryption key provider health before rebooting");
+                true
+            }
+            _ => false,
+        };
+        if check_key_provider {
+            self.api_manager()
+                .get_encryption_key()
+                .await
+                .change_context(RebootError::GetKeyFailed)?;
+            info!("Remote encryption key is available");
         }
 
-        info!("Rebooting system");
+        if coordination {
+            self.acquire_reboot_lease().await?;
+        }
+
+        let result = match hooks::run_pre_reboot_hooks(&self.config, &self.state).await {
+            Ok(()) => {
+                info!("Rebooting system");
+                Self::run_reboot_command().await
+            }
+            Err(e) => Err(e.change_context(RebootError::PreRebootHookFailed)),
+        };
+        if result.is_err() && coordination {
+            self.release_reboot_lease().await;
+        }
+        result
+    }
+
+    async fn run_reboot_command() -> Result<(), RebootError> {
         let status = Command::new("sudo")
             .arg("reboot")
             .status()
@@ -239,29 +390,74 @@ impl RebootManager {
         Ok(())
     }
 
-    pub async fn sleep_until_reboot_check(config: &Config) -> Result<(), RebootError> {
+    /// The lease held flag is saved to the state file, so that the lease
+    /// can be released after the reboot.
+    async fn acquire_reboot_lease(&self) -> Result<(), RebootError> {
+        info!("Acquiring reboot lease");
+        let lease = self
+            .api_manager()
+            .acquire_reboot_lease()
+            .await
+            .change_context(RebootError::RebootLeaseFailed)?;
+
+        if !lease.granted {
+            return Err(Report::new(RebootError::RebootLeaseNotGranted)
+                .attach_printable(format!("Current lease holders: {:?}", lease.holders)));
+        }
+
+        info!("Reboot lease granted");
+        self.state
+            .modify(|s| s.persistent.reboot_lease_held = true)
+            .await;
+        Ok(())
+    }
+
+    /// Release failures are only logged, as the coordinator removes the
+    /// lease when it expires.
+    async fn release_reboot_lease(&self) {
+        match self.api_manager().release_reboot_lease().await {
+            Ok(()) => info!("Reboot lease released"),
+            Err(e) => warn!(
+                "Reboot lease releasing failed. The lease expires later. Error: {:?}",
+                e
+            ),
+        }
+        self.state
+            .modify(|s| s.persistent.reboot_lease_held = false)
+            .await;
+    }
+
+    /// Sleep until the current or the next maintenance window. The next
+    /// reboot check time is stored to the state.
+    pub async fn sleep_until_reboot_check(
+        config: &Config,
+        state: &StateStorage,
+        previous_check: Option<OffsetDateTime>,
+    ) -> Result<(), RebootError> {
         debug!("Calculating sleep time");
 
         let now = OffsetDateTime::now_utc();
-
-        let target_time = if let Some(reboot) = config.reboot_if_needed() {
-            Time::from_hms(reboot.time.hours, reboot.time.minutes, 0)
-                .change_context(RebootError::TimeError)?
-        } else {
+        let earliest_check = previous_check
+            .map(|previous| previous + REBOOT_CHECK_INTERVAL)
+            .filter(|earliest| *earliest > now)
+            .unwrap_or(now);
+
+        let next_check = config
+            .reloadable()
+            .reboot_if_needed()
+            .and_then(|reboot| reboot.next_check_time(earliest_check));
+        let Some(next_check) = next_check else {
+            METRICS.next_reboot_check(None);
+            state.modify(|s| s.next_reboot_check = None).await;
             futures::future::pending::<()>().await;
             return Err(RebootError::ConfigError.into());
         };
 
-        let target_date_time = now.replace_time(target_time);
-
-        let duration = if target_date_time > now {
-            target_date_time - now
-        } else {
-            let tomorrow = now + Duration::from_secs(24 * 60 * 60);
-            let tomorrow_target_date_time = tomorrow.replace_time(target_time);
-            tomorrow_target_date_time - now
-        };
-
+        METRICS.next_reboot_check(Some(next_check));
+        state
+            .modify(|s| s.next_reboot_check = Some(next_check))
+            .await;
+        let duration = next_check - now;
         debug!("Time until reboot check: {}", duration);
         sleep(duration.unsigned_abs()).await;
 
@@ -0,0 +1,198 @@
+//! Grant reboot leases to other managers, so that only some servers
+//! reboot at the same time.
+
+use error_stack::Result;
+use manager_model::RebootLease;
+use time::OffsetDateTime;
+use tracing::info;
+
+use super::state::{RebootLeaseRecord, StateStorage};
+use crate::config::Config;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CoordinatorError {
+    #[error("Reboot coordinator is not configured")]
+    NotConfigured,
+}
+
+pub struct RebootCoordinator<'a> {
+    config: &'a Config,
+    state: &'a StateStorage,
+}
+
+impl<'a> RebootCoordinator<'a> {
+    pub fn new(config: &'a Config, state: &'a StateStorage) -> Self {
+        Self { config, state }
+    }
+
+    /// Lease is renewed if the server already holds it. Expired leases
+    /// are removed.
+    pub async fn acquire(&self, name: &str) -> Result<RebootLease, CoordinatorError> {
+        let config = self
+            .config
+            .reboot_coordinator()
+            .ok_or(CoordinatorError::NotConfigured)?;
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let lease_timeout = i64::try_from(config.lease_timeout().as_secs()).unwrap_or(i64::MAX);
+        let expires_time = now.saturating_add(lease_timeout);
+
+        let lease = self
+            .state
+            .modify(|s| {
+                let leases = &mut s.persistent.reboot_leases;
+                leases.retain(|lease| lease.expires_time > now);
+                let granted = if let Some(lease) = leases.iter_mut().find(|l| l.name == name) {
+                    lease.expires_time = expires_time;
+                    true
+                } else if leases.len() < config.max_concurrent_reboots() {
+                    leases.push(RebootLeaseRecord {
+                        name: name.to_string(),
+                        expires_time,
+                    });
+                    true
+                } else {
+                    false
+                };
+                RebootLease {
+                    granted,
+                    holders: leases.iter().map(|lease| lease.name.clone()).collect(),
+                }
+            })
+            .await;
+
+        if lease.granted {
+            info!("Reboot lease granted to {}", name);
+        } else {
+            info!(
+                "Reboot lease denied from {}. Current holders: {:?}",
+                name, lease.holders
+            );
+        }
+
+        Ok(lease)
+    }
+
+    pub async fn release(&self, name: &str) -> Result<(), CoordinatorError> {
+        if self.config.reboot_coordinator().is_none() {
+            return Err(CoordinatorError::NotConfigured.into());
+        }
+
+        let released = self
+            .state
+            .modify(|s| {
+                let leases = &mut s.persistent.reboot_leases;
+                let count = leases.len();
+                leases.retain(|lease| lease.name != name);
+                leases.len() != count
+            })
+            .await;
+
+        if released {
+            info!("Reboot lease released by {}", name);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::load_test_config;
+
+    const COORDINATOR: &str = r#"
+[reboot_coordinator]
+max_concurrent_reboots = 2
+lease_timeout_seconds = 600
+"#;
+
+    async fn leases(state: &StateStorage) -> Vec<RebootLeaseRecord> {
+        state.get(|s| s.persistent.reboot_leases.clone()).await
+    }
+
+    #[tokio::test]
+    async fn leases_are_limited_by_max_concurrent_reboots() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), COORDINATOR);
+        let state = StateStorage::new(&config);
+        let coordinator = RebootCoordinator::new(&config, &state);
+
+        assert!(coordinator.acquire("server1").await.unwrap().granted);
+        assert!(coordinator.acquire("server2").await.unwrap().granted);
+        let lease = coordinator.acquire("server3").await.unwrap();
+
+        assert!(!lease.granted);
+        assert_eq!(lease.holders, ["server1", "server2"]);
+    }
+
+    #[tokio::test]
+    async fn acquiring_again_renews_lease() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), COORDINATOR);
+        let state = StateStorage::new(&config);
+        let coordinator = RebootCoordinator::new(&config, &state);
+
+        coordinator.acquire("server1").await.unwrap();
+        state
+            .modify(|s| s.persistent.reboot_leases[0].expires_time -= 100)
+            .await;
+        let before = OffsetDateTime::now_utc().unix_timestamp();
+        let lease = coordinator.acquire("server1").await.unwrap();
+
+        assert!(lease.granted);
+        assert_eq!(lease.holders, ["server1"]);
+        let leases = leases(&state).await;
+        assert_eq!(leases.len(), 1);
+        assert!(leases[0].expires_time >= before + 600);
+    }
+
+    #[tokio::test]
+    async fn expired_lease_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), COORDINATOR);
+        let state = StateStorage::new(&config);
+        let coordinator = RebootCoordinator::new(&config, &state);
+
+        coordinator.acquire("server1").await.unwrap();
+        coordinator.acquire("server2").await.unwrap();
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        state
+            .modify(|s| s.persistent.reboot_leases[0].expires_time = now - 1)
+            .await;
+        let lease = coordinator.acquire("server3").await.unwrap();
+
+        assert!(lease.granted);
+        assert_eq!(lease.holders, ["server2", "server3"]);
+    }
+
+    #[tokio::test]
+    async fn releasing_lease_allows_next_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(
+            dir.path(),
+            "[reboot_coordinator]\nmax_concurrent_reboots = 1\n",
+        );
+        let state = StateStorage::new(&config);
+        let coordinator = RebootCoordinator::new(&config, &state);
+
+        coordinator.acquire("server1").await.unwrap();
+        assert!(!coordinator.acquire("server2").await.unwrap().granted);
+
+        coordinator.release("unknown").await.unwrap();
+        assert_eq!(leases(&state).await.len(), 1);
+
+        coordinator.release("server1").await.unwrap();
+        assert!(coordinator.acquire("server2").await.unwrap().granted);
+    }
+
+    #[tokio::test]
+    async fn coordinator_must_be_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), "");
+        let state = StateStorage::new(&config);
+        let coordinator = RebootCoordinator::new(&config, &state);
+
+        assert!(coordinator.acquire("server1").await.is_err());
+        assert!(coordinator.release("server1").await.is_err());
+    }
+}
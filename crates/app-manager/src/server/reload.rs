@@ -0,0 +1,66 @@
+//! Reload config file without restarting the manager
+
+use std::sync::Arc;
+
+use error_stack::{Result, ResultExt};
+use manager_model::ConfigReloadReport;
+use tracing::{info, warn};
+
+use super::{client::ApiClient, reboot::RebootManagerHandle};
+use crate::config::Config;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ReloadError {
+    #[error("Config reloading failed")]
+    ConfigReloadFailed,
+}
+
+#[derive(Debug)]
+pub struct ConfigReloader {
+    config: Arc<Config>,
+    api_client: Arc<ApiClient>,
+    reboot_manager: RebootManagerHandle,
+}
+
+impl ConfigReloader {
+    pub fn new(
+        config: Arc<Config>,
+        api_client: Arc<ApiClient>,
+        reboot_manager: RebootManagerHandle,
+    ) -> Self {
+        Self {
+            config,
+            api_client,
+            reboot_manager,
+        }
+    }
+
+    /// Reload config file. Current config stays in use if the new
+    /// config is invalid.
+    pub async fn reload(&self) -> Result<ConfigReloadReport, ReloadError> {
+        let report = self
+            .config
+            .reload()
+            .change_context(ReloadError::ConfigReloadFailed)?;
+
+        self.api_client
+            .reload_system_info_remote_managers(&self.config);
+
+        if let Err(e) = self.reboot_manager.config_reloaded().await {
+            warn!(
+                "Notifying reboot manager about config reload failed. Error: {:?}",
+                e
+            );
+        }
+
+        info!("Config reloaded. Reloaded: {:?}", report.reloaded);
+        if !report.restart_required.is_empty() {
+            warn!(
+                "Config changes which require restart: {:?}",
+                report.restart_required
+            );
+        }
+
+        Ok(report)
+    }
+}
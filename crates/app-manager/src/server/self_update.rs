@@ -0,0 +1,106 @@
+//! Restart manager using the updated binary without rebooting
+
+use std::{os::unix::process::CommandExt, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::mount::MountMode;
+
+/// Environment variable which contains the handoff state JSON for
+/// the new manager process.
+pub const HANDOFF_ENV_VAR: &str = "APP_MANAGER_HANDOFF";
+
+/// State which the previous manager process passes to the new one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffState {
+    /// Value of MountMode::as_str()
+    pub mount_mode: String,
+    /// Backend was left running and it should not be started again.
+    pub backend_running: bool,
+}
+
+impl HandoffState {
+    pub fn new(mount_mode: MountMode, backend_running: bool) -> Self {
+        Self {
+            mount_mode: mount_mode.as_str().to_string(),
+            backend_running,
+        }
+    }
+
+    /// Read and remove the handoff environment variable. Call this
+    /// before starting other threads.
+    pub fn take_from_env() -> Option<Self> {
+        let value = std::env::var(HANDOFF_ENV_VAR).ok()?;
+        std::env::remove_var(HANDOFF_ENV_VAR);
+        match serde_json::from_str(&value) {
+            Ok(state) => Some(state),
+            Err(e) => {
+                eprintln!("Invalid {} value. Error: {}", HANDOFF_ENV_VAR, e);
+                None
+            }
+        }
+    }
+
+    pub fn mount_mode(&self) -> Option<MountMode> {
+        MountMode::from_str_value(&self.mount_mode)
+    }
+}
+
+/// New manager binary and the state for it.
+#[derive(Debug)]
+pub struct ManagerHandoff {
+    pub binary: PathBuf,
+    pub state: HandoffState,
+}
+
+impl ManagerHandoff {
+    /// Replace the current process with the new manager binary using
+    /// the same command line arguments. Returns only if exec fails.
+    pub fn exec(self) -> std::io::Error {
+        let state = match serde_json::to_string(&self.state) {
+            Ok(state) => state,
+            Err(e) => return std::io::Error::other(e),
+        };
+        std::process::Command::new(&self.binary)
+            .args(std::env::args_os().skip(1))
+            .env(HANDOFF_ENV_VAR, state)
+            .exec()
+    }
+}
+
+/// Request manager restart after the manager binary is updated.
+#[derive(Debug, Clone)]
+pub struct SelfUpdateHandle {
+    sender: mpsc::Sender<()>,
+}
+
+impl SelfUpdateHandle {
+    pub fn request_restart(&self) {
+        match self.sender.try_send(()) {
+            Ok(()) => info!("Manager restart requested"),
+            Err(mpsc::error::TrySendError::Full(())) => info!("Manager restart already requested"),
+            Err(mpsc::error::TrySendError::Closed(())) => {
+                warn!("Manager restart request failed. Manager is quitting.")
+            }
+        }
+    }
+}
+
+pub struct SelfUpdateReceiver {
+    receiver: mpsc::Receiver<()>,
+}
+
+impl SelfUpdateReceiver {
+    pub async fn wait_restart_request(&mut self) {
+        if self.receiver.recv().await.is_none() {
+            futures::future::pending().await
+        }
+    }
+}
+
+pub fn self_update_channel() -> (SelfUpdateHandle, SelfUpdateReceiver) {
+    let (sender, receiver) = mpsc::channel(1);
+    (SelfUpdateHandle { sender }, SelfUpdateReceiver { receiver })
+}
@@ -1,16 +1,203 @@
 //! State storage
 //!
+//! Part of the state is saved to a file in the storage directory, so
+//! that it is available after the manager restarts.
 
-use super::mount::MountState;
+use std::path::{Path, PathBuf};
 
+use error_stack::{Result, ResultExt};
+use manager_model::{CommandOutput, RebootAttempt, RebootReason, SoftwareOptions, UpdateStatus};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+use super::{
+    metrics::METRICS,
+    mount::{MountMode, MountState},
+};
+use crate::{
+    config::{file::ConfigFile, Config},
+    utils::ContextExt,
+};
+
+pub const STATE_FILE_NAME: &str = "manager_state.json";
+
+#[derive(thiserror::Error, Debug)]
+pub enum StateError {
+    #[error("Reading state file failed")]
+    ReadFailed,
+
+    #[error("Parsing state file failed")]
+    ParseFailed,
+
+    #[error("Serializing state failed")]
+    SerializeFailed,
+
+    #[error("Writing state file failed")]
+    WriteFailed,
+
+    #[error("Loading config file failed")]
+    ConfigLoadFailed,
+
+    #[error("State file not found")]
+    StateFileNotFound,
+}
+
+/// State which is saved to the state file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistentState {
+    /// Reboot system when the reboot manager checks next time.
+    pub pending_reboot: Option<RebootReason>,
+    pub last_reboot_attempt: Option<RebootAttempt>,
+    /// Reboot lease from the reboot coordinator should be released after
+    /// the manager starts.
+    pub reboot_lease_held: bool,
+    /// Leases which the reboot coordinator has granted.
+    pub reboot_leases: Vec<RebootLeaseRecord>,
+    /// Manager API key has been guessed and the manager API is locked.
+    pub api_locked: bool,
+    /// Value of MountMode::as_str()
+    pub mount_mode: Option<String>,
+    pub last_update: Option<UpdateResult>,
+    pub last_build: Option<BuildResult>,
+    pub last_pre_reboot_hooks: Option<HookResults>,
+    pub last_post_boot_hooks: Option<HookResults>,
+    /// Boot ID from the boot when the post-boot hooks were handled.
+    pub boot_id: Option<String>,
+}
+
+impl PersistentState {
+    pub fn mount_mode(&self) -> Option<MountMode> {
+        self.mount_mode
+            .as_deref()
+            .and_then(MountMode::from_str_value)
+    }
+
+    /// Flags which are already set are kept.
+    fn merge(&mut self, loaded: PersistentState) {
+        self.api_locked |= loaded.api_locked;
+        self.reboot_lease_held |= loaded.reboot_lease_held;
+        if self.reboot_leases.is_empty() {
+            self.reboot_leases = loaded.reboot_leases;
+        }
+        if self.pending_reboot.is_none() {
+            self.pending_reboot = loaded.pending_reboot;
+        }
+        if self.last_reboot_attempt.is_none() {
+            self.last_reboot_attempt = loaded.last_reboot_attempt;
+        }
+        if self.mount_mode.is_none() {
+            self.mount_mode = loaded.mount_mode;
+        }
+        if self.last_update.is_none() {
+            self.last_update = loaded.last_update;
+        }
+        if self.last_build.is_none() {
+            self.last_build = loaded.last_build;
+        }
+        if self.last_pre_reboot_hooks.is_none() {
+            self.last_pre_reboot_hooks = loaded.last_pre_reboot_hooks;
+        }
+        if self.last_post_boot_hooks.is_none() {
+            self.last_post_boot_hooks = loaded.last_post_boot_hooks;
+        }
+        if self.boot_id.is_none() {
+            self.boot_id = loaded.boot_id;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RebootLeaseRecord {
+    pub name: String,
+    /// Unix timestamp
+    pub expires_time: i64,
+}
+
+/// Latest software update or backend restart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateResult {
+    /// Unix timestamp
+    pub finished_time: i64,
+    pub status: UpdateStatus,
+}
+
+impl UpdateResult {
+    pub fn new(status: UpdateStatus) -> Self {
+        Self {
+            finished_time: OffsetDateTime::now_utc().unix_timestamp(),
+            status,
+        }
+    }
+}
+
+/// Latest software build.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuildResult {
+    pub software: SoftwareOptions,
+    /// Unix timestamp
+    pub finished_time: i64,
+    /// None if the build succeeded.
+    pub error: Option<String>,
+}
+
+impl BuildResult {
+    pub fn new(software: SoftwareOptions, error: Option<String>) -> Self {
+        Self {
+            software,
+            finished_time: OffsetDateTime::now_utc().unix_timestamp(),
+            error,
+        }
+    }
+}
+
+/// Outputs of hooks which were run. Running pre-reboot hooks stops at
+/// the hook which aborted the reboot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookResults {
+    /// Unix timestamp
+    pub finished_time: i64,
+    pub outputs: Vec<CommandOutput>,
+}
+
+impl HookResults {
+    pub fn new(outputs: Vec<CommandOutput>) -> Self {
+        Self {
+            finished_time: OffsetDateTime::now_utc().unix_timestamp(),
+            outputs,
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct State {
     pub mount_state: MountState,
+    pub persistent: PersistentState,
+    /// Not saved to the state file.
+    pub next_reboot_check: Option<OffsetDateTime>,
+    state_file_loaded: bool,
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
             mount_state: MountState::new(),
+            persistent: PersistentState::default(),
+            next_reboot_check: None,
+            state_file_loaded: false,
+        }
+    }
+
+    /// Mount mode is saved only when storage is mounted, so that
+    /// the previous mode is available if the manager is restarted
+    /// before mounting. The mount manager clears the saved mode when
+    /// storage is unmounted or found to be not mounted.
+    fn sync_mount_mode(&mut self) {
+        let mode = self.mount_state.mode();
+        if mode != MountMode::NotMounted {
+            self.persistent.mount_mode = Some(mode.as_str().to_string());
         }
     }
 }
@@ -21,30 +208,180 @@ impl Default for State {
     }
 }
 
+#[derive(Debug)]
 pub struct StateStorage {
     state: tokio::sync::Mutex<State>,
+    file: PathBuf,
 }
 
 impl StateStorage {
-    pub fn new() -> Self {
+    pub fn new(config: &Config) -> Self {
         Self {
             state: tokio::sync::Mutex::new(State::new()),
+            file: config.storage_dir().join(STATE_FILE_NAME),
+        }
+    }
+
+    /// Load the state file if it exists and merge it with the current
+    /// state. This can be called again after the storage directory
+    /// becomes available. Does nothing if the file is already loaded.
+    pub async fn load(&self) {
+        let mut state = self.state.lock().await;
+        if state.state_file_loaded {
+            return;
         }
+        match read_state_file(&self.file).await {
+            Ok(Some(loaded)) => {
+                info!("State loaded from {}", self.file.display());
+                state.state_file_loaded = true;
+                state.persistent.merge(loaded);
+                if state.persistent.api_locked {
+                    warn!(
+                        "Manager API is locked because API key was guessed. Stop the manager and run 'app-manager unlock-api' to unlock the API."
+                    );
+                }
+            }
+            Ok(None) => info!("State file {} does not exist", self.file.display()),
+            Err(e) => warn!("State loading failed. Error: {:?}", e),
+        }
+        METRICS.api_locked_changed(state.persistent.api_locked);
+        state.sync_mount_mode();
+        self.save(&state.persistent).await;
     }
 
-    pub async fn get<T>(&self, action: impl Fn(&State) -> T) -> T {
+    pub async fn get<T>(&self, action: impl FnOnce(&State) -> T) -> T {
         let state = self.state.lock().await;
         action(&state)
     }
 
-    pub async fn modify<T>(&self, action: impl Fn(&mut State) -> T) -> T {
+    /// The state file is updated if the persistent state changes.
+    pub async fn modify<T>(&self, action: impl FnOnce(&mut State) -> T) -> T {
         let mut state = self.state.lock().await;
-        action(&mut state)
+        let previous = state.persistent.clone();
+        let value = action(&mut state);
+        state.sync_mount_mode();
+        if state.persistent.api_locked != previous.api_locked {
+            METRICS.api_locked_changed(state.persistent.api_locked);
+        }
+        if state.persistent != previous {
+            self.save(&state.persistent).await;
+        }
+        value
+    }
+
+    /// Saving is skipped until the storage directory exists. The state
+    /// is merged with the state file when it is loaded again.
+    async fn save(&self, state: &PersistentState) {
+        if !self.file.parent().is_some_and(|dir| dir.exists()) {
+            return;
+        }
+        if let Err(e) = write_state_file(&self.file, state).await {
+            warn!("State saving failed. Error: {:?}", e);
+        }
     }
 }
 
-impl Default for StateStorage {
-    fn default() -> Self {
-        Self::new()
+/// Set api_locked to false in the state file. The manager must not be
+/// running, because it would save the lock again.
+///
+/// Missing state file is an error, because the storage directory can be
+/// on secure storage which is not mounted when the manager is stopped.
+pub async fn handle_unlock_api_mode(config_file: &Path) -> Result<(), StateError> {
+    let file =
+        ConfigFile::load_config_file(config_file).change_context(StateError::ConfigLoadFailed)?;
+    let path = file.storage_dir.join(STATE_FILE_NAME);
+
+    let Some(mut state) = read_state_file(&path).await? else {
+        return Err(StateError::StateFileNotFound.report()).attach_printable(format!(
+            "{} does not exist. Mount secure storage if the storage directory is located there.",
+            path.display()
+        ));
+    };
+
+    if state.api_locked {
+        state.api_locked = false;
+        write_state_file(&path, &state).await?;
+        println!("Manager API unlocked. Start the manager to use the API.");
+    } else {
+        println!("Manager API is not locked");
+    }
+
+    Ok(())
+}
+
+/// Returns None if the file does not exist.
+async fn read_state_file(path: &Path) -> Result<Option<PersistentState>, StateError> {
+    let data = match tokio::fs::read(path).await {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).change_context(StateError::ReadFailed),
+    };
+    let state = serde_json::from_slice(&data)
+        .change_context(StateError::ParseFailed)
+        .attach_printable_lazy(|| path.display().to_string())?;
+    Ok(Some(state))
+}
+
+/// Write to a temporary file and rename it, so that the state file is
+/// never partially written.
+async fn write_state_file(path: &Path, state: &PersistentState) -> Result<(), StateError> {
+    let data = serde_json::to_vec_pretty(state).change_context(StateError::SerializeFailed)?;
+    let tmp_path = path.with_extension("json.tmp");
+
+    let mut file = tokio::fs::File::create(&tmp_path)
+        .await
+        .change_context(StateError::WriteFailed)
+        .attach_printable_lazy(|| tmp_path.display().to_string())?;
+    file.write_all(&data)
+        .await
+        .change_context(StateError::WriteFailed)?;
+    file.sync_all()
+        .await
+        .change_context(StateError::WriteFailed)?;
+    drop(file);
+
+    tokio::fs::rename(&tmp_path, path)
+        .await
+        .change_context(StateError::WriteFailed)
+        .attach_printable_lazy(|| path.display().to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{file::CONFIG_FILE_NAME, load_test_config};
+
+    #[tokio::test]
+    async fn unlock_api_fails_when_state_file_is_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), "");
+        std::fs::remove_dir(config.storage_dir()).unwrap();
+
+        let result = handle_unlock_api_mode(&dir.path().join(CONFIG_FILE_NAME)).await;
+
+        let error = result.unwrap_err();
+        assert!(matches!(
+            error.current_context(),
+            StateError::StateFileNotFound
+        ));
+    }
+
+    #[tokio::test]
+    async fn unlock_api_removes_lock_from_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = load_test_config(dir.path(), "");
+        let state = StateStorage::new(&config);
+        state.load().await;
+        state.modify(|s| s.persistent.api_locked = true).await;
+
+        handle_unlock_api_mode(&dir.path().join(CONFIG_FILE_NAME))
+            .await
+            .unwrap();
+
+        let file = config.storage_dir().join(STATE_FILE_NAME);
+        let loaded = read_state_file(&file).await.unwrap().unwrap();
+        assert!(!loaded.api_locked);
     }
 }
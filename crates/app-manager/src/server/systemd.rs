@@ -0,0 +1,360 @@
+//! Control systemd units using systemd D-Bus API
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use error_stack::{Result, ResultExt};
+use futures::{Stream, StreamExt};
+use tracing::info;
+use zbus::{zvariant::OwnedObjectPath, Connection};
+
+use crate::utils::ContextExt;
+
+/// Maximum time to wait for a start, stop or restart job to complete.
+const JOB_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Value of ExecMainCode when the main process exited normally.
+const CLD_EXITED: i32 = 1;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SystemdError {
+    #[error("D-Bus connection failed")]
+    ConnectionFailed,
+
+    #[error("D-Bus method call failed")]
+    MethodCallFailed,
+
+    #[error("Reading unit property failed")]
+    PropertyReadFailed,
+
+    #[error("Waiting job completion failed")]
+    JobWaitFailed,
+
+    #[error("Job timeout")]
+    JobTimeout,
+
+    #[error("Job failed with result: {0}")]
+    JobFailed(String),
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.systemd1.Manager",
+    default_service = "org.freedesktop.systemd1",
+    default_path = "/org/freedesktop/systemd1"
+)]
+trait SystemdManager {
+    fn start_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+
+    fn stop_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+
+    fn restart_unit(&self, name: &str, mode: &str) -> zbus::Result<OwnedObjectPath>;
+
+    fn load_unit(&self, name: &str) -> zbus::Result<OwnedObjectPath>;
+
+    /// Enables job signals for this client.
+    fn subscribe(&self) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn job_removed(
+        &self,
+        id: u32,
+        job: OwnedObjectPath,
+        unit: String,
+        result: String,
+    ) -> zbus::Result<()>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.systemd1.Unit",
+    default_service = "org.freedesktop.systemd1"
+)]
+trait SystemdUnit {
+    #[zbus(property)]
+    fn active_state(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn sub_state(&self) -> zbus::Result<String>;
+
+    /// Microseconds since Unix epoch
+    #[zbus(property)]
+    fn active_enter_timestamp(&self) -> zbus::Result<u64>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.systemd1.Service",
+    default_service = "org.freedesktop.systemd1"
+)]
+trait SystemdService {
+    #[zbus(property, name = "MainPID")]
+    fn main_pid(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn exec_main_code(&self) -> zbus::Result<i32>;
+
+    #[zbus(property)]
+    fn exec_main_status(&self) -> zbus::Result<i32>;
+}
+
+/// Values from JobRemoved signal
+#[derive(Debug, Clone)]
+struct RemovedJob {
+    job: OwnedObjectPath,
+    result: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum UnitAction {
+    Start,
+    Stop,
+    Restart,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SystemdUnitStatus {
+    pub active_state: String,
+    pub sub_state: String,
+    /// Main process PID if the unit is a service which is running.
+    pub main_pid: Option<u32>,
+    /// Time since the unit entered active state.
+    pub active_duration: Option<Duration>,
+    /// Exit code of the previous main process. None if the process
+    /// has not exited or it was terminated by a signal.
+    pub last_exit_code: Option<i32>,
+}
+
+impl SystemdUnitStatus {
+    pub fn is_active(&self) -> bool {
+        self.active_state == "active"
+    }
+}
+
+#[derive(Debug)]
+pub struct SystemdController {
+    unit: String,
+}
+
+impl SystemdController {
+    pub fn new(unit: String) -> Self {
+        Self { unit }
+    }
+
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    pub async fn start(&self) -> Result<(), SystemdError> {
+        self.run_job(UnitAction::Start).await
+    }
+
+    pub async fn stop(&self) -> Result<(), SystemdError> {
+        self.run_job(UnitAction::Stop).await
+    }
+
+    pub async fn restart(&self) -> Result<(), SystemdError> {
+        self.run_job(UnitAction::Restart).await
+    }
+
+    pub async fn is_active(&self) -> Result<bool, SystemdError> {
+        Ok(self.status().await?.is_active())
+    }
+
+    pub async fn status(&self) -> Result<SystemdUnitStatus, SystemdError> {
+        let connection = Self::connect().await?;
+        let manager = SystemdManagerProxy::new(&connection)
+            .await
+            .change_context(SystemdError::ConnectionFailed)?;
+        let path = manager
+            .load_unit(&self.unit)
+            .await
+            .change_context(SystemdError::MethodCallFailed)?;
+
+        let unit = SystemdUnitProxy::builder(&connection)
+            .path(path.clone())
+            .change_context(SystemdError::ConnectionFailed)?
+            .build()
+            .await
+            .change_context(SystemdError::ConnectionFailed)?;
+        let active_state = unit
+            .active_state()
+            .await
+            .change_context(SystemdError::PropertyReadFailed)?;
+        let sub_state = unit
+            .sub_state()
+            .await
+            .change_context(SystemdError::PropertyReadFailed)?;
+        let active_enter_timestamp = unit
+            .active_enter_timestamp()
+            .await
+            .change_context(SystemdError::PropertyReadFailed)?;
+
+        let mut status = SystemdUnitStatus {
+            active_duration: (active_state == "active")
+                .then(|| duration_since_timestamp(active_enter_timestamp))
+                .flatten(),
+            active_state,
+            sub_state,
+            ..Default::default()
+        };
+
+        // Other unit types do not have the service interface.
+        if self.unit.ends_with(".service") {
+            let service = SystemdServiceProxy::builder(&connection)
+                .path(path)
+                .change_context(SystemdError::ConnectionFailed)?
+                .build()
+                .await
+                .change_context(SystemdError::ConnectionFailed)?;
+            let main_pid = service
+                .main_pid()
+                .await
+                .change_context(SystemdError::PropertyReadFailed)?;
+            let exec_main_code = service
+                .exec_main_code()
+                .await
+                .change_context(SystemdError::PropertyReadFailed)?;
+            status.main_pid = Some(main_pid).filter(|pid| *pid != 0);
+            if exec_main_code == CLD_EXITED {
+                status.last_exit_code = Some(
+                    service
+                        .exec_main_status()
+                        .await
+                        .change_context(SystemdError::PropertyReadFailed)?,
+                );
+            }
+        }
+
+        Ok(status)
+    }
+
+    /// Start the job and wait until systemd reports the job result.
+    async fn run_job(&self, action: UnitAction) -> Result<(), SystemdError> {
+        let connection = Self::connect().await?;
+        let manager = SystemdManagerProxy::new(&connection)
+            .await
+            .change_context(SystemdError::ConnectionFailed)?;
+        manager
+            .subscribe()
+            .await
+            .change_context(SystemdError::MethodCallFailed)?;
+        // Create the signal stream before starting the job, so that the
+        // job result is not missed.
+        let job_removed = manager
+            .receive_job_removed()
+            .await
+            .change_context(SystemdError::MethodCallFailed)?
+            .map(|signal| {
+                let args = signal.args().change_context(SystemdError::JobWaitFailed)?;
+                Ok(RemovedJob {
+                    job: args.job.clone(),
+                    result: args.result.clone(),
+                })
+            });
+
+        info!("Systemd unit {}: {:?}", self.unit, action);
+        let job = match action {
+            UnitAction::Start => manager.start_unit(&self.unit, "replace").await,
+            UnitAction::Stop => manager.stop_unit(&self.unit, "replace").await,
+            UnitAction::Restart => manager.restart_unit(&self.unit, "replace").await,
+        }
+        .change_context(SystemdError::MethodCallFailed)?;
+
+        wait_job(&job, job_removed, JOB_TIMEOUT).await
+    }
+
+    async fn connect() -> Result<Connection, SystemdError> {
+        Connection::system()
+            .await
+            .change_context(SystemdError::ConnectionFailed)
+    }
+}
+
+/// Wait until the job is removed and check the job result. Signals for
+/// other jobs are ignored.
+async fn wait_job(
+    job: &OwnedObjectPath,
+    job_removed: impl Stream<Item = Result<RemovedJob, SystemdError>>,
+    timeout: Duration,
+) -> Result<(), SystemdError> {
+    let wait_result = async {
+        let mut job_removed = std::pin::pin!(job_removed);
+        while let Some(removed) = job_removed.next().await {
+            let removed = removed?;
+            if removed.job == *job {
+                return Ok(removed.result);
+            }
+        }
+        Err(SystemdError::JobWaitFailed.report())
+    };
+
+    let result = tokio::time::timeout(timeout, wait_result)
+        .await
+        .change_context(SystemdError::JobTimeout)??;
+
+    if result == "done" {
+        Ok(())
+    } else {
+        Err(SystemdError::JobFailed(result).report())
+    }
+}
+
+fn duration_since_timestamp(micros: u64) -> Option<Duration> {
+    if micros == 0 {
+        return None;
+    }
+    let time = UNIX_EPOCH + Duration::from_micros(micros);
+    SystemTime::now().duration_since(time).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use super::*;
+
+    fn job(id: u32) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(format!("/org/freedesktop/systemd1/job/{}", id)).unwrap()
+    }
+
+    fn removed(id: u32, result: &str) -> Result<RemovedJob, SystemdError> {
+        Ok(RemovedJob {
+            job: job(id),
+            result: result.to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn job_wait_succeeds_when_job_is_done() {
+        let signals = stream::iter([removed(1, "failed"), removed(2, "done")]);
+        let result = wait_job(&job(2), signals, JOB_TIMEOUT).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn job_wait_fails_when_job_fails() {
+        let signals = stream::iter([removed(1, "done"), removed(2, "failed")]);
+        let result = wait_job(&job(2), signals, JOB_TIMEOUT).await;
+        assert!(matches!(
+            result.unwrap_err().current_context(),
+            SystemdError::JobFailed(result) if result == "failed"
+        ));
+    }
+
+    #[tokio::test]
+    async fn job_wait_fails_when_signal_stream_ends() {
+        let signals = stream::iter([removed(1, "done")]);
+        let result = wait_job(&job(2), signals, JOB_TIMEOUT).await;
+        assert!(matches!(
+            result.unwrap_err().current_context(),
+            SystemdError::JobWaitFailed
+        ));
+    }
+
+    #[tokio::test]
+    async fn job_wait_times_out() {
+        let signals = stream::iter([removed(1, "done")]).chain(stream::pending());
+        let result = wait_job(&job(2), signals, Duration::from_millis(10)).await;
+        assert!(matches!(
+            result.unwrap_err().current_context(),
+            SystemdError::JobTimeout
+        ));
+    }
+}
@@ -3,19 +3,26 @@
 use std::{
     path::{Path, PathBuf},
     process::ExitStatus,
-    sync::{atomic::Ordering, Arc},
+    sync::Arc,
 };
 
 use error_stack::{Result, ResultExt};
-use manager_model::{BuildInfo, ResetDataQueryParam, SoftwareInfo, SoftwareOptions};
+use manager_model::{
+    BuildInfo, RebootReason, ResetDataQueryParam, SoftwareInfo, SoftwareOptions, UpdateStatus,
+    UpdateStep,
+};
 use tokio::{process::Command, task::JoinHandle};
 use tracing::{info, warn};
 
+use self::status::UpdateStatusTracker;
 use super::{
     backend_controller::BackendController,
     build::BuildDirCreator,
     client::{ApiClient, ApiManager},
-    reboot::{RebootManagerHandle, REBOOT_ON_NEXT_CHECK},
+    metrics::METRICS,
+    reboot::RebootManagerHandle,
+    self_update::SelfUpdateHandle,
+    state::{StateStorage, UpdateResult},
     ServerQuitWatcher,
 };
 use crate::{
@@ -23,6 +30,8 @@ use crate::{
     utils::{ContextExt, InProgressChannel, InProgressReceiver, InProgressSender},
 };
 
+pub mod status;
+
 #[derive(thiserror::Error, Debug)]
 pub enum UpdateError {
     #[error("Update manager related config is missing")]
@@ -87,6 +96,9 @@ pub enum UpdateError {
 
     #[error("Start backend failed")]
     StartBackendFailed,
+
+    #[error("Restart backend failed")]
+    RestartBackendFailed,
 }
 
 #[derive(Debug)]
@@ -121,6 +133,7 @@ pub enum UpdateManagerMessage {
 
 pub struct UpdateManagerHandle {
     sender: InProgressSender<UpdateManagerMessage>,
+    status: UpdateStatusTracker,
 }
 
 impl UpdateManagerHandle {
@@ -146,6 +159,10 @@ impl UpdateManagerHandle {
         self.send_message(message).await
     }
 
+    pub fn update_status(&self) -> UpdateStatus {
+        self.status.status()
+    }
+
     async fn send_message(&self, message: UpdateManagerMessage) -> Result<(), UpdateError> {
         self.sender
             .send_message(message)
@@ -160,6 +177,10 @@ pub struct UpdateManager {
     api_client: Arc<ApiClient>,
     receiver: InProgressReceiver<UpdateManagerMessage>,
     reboot_manager_handle: RebootManagerHandle,
+    backend_controller: BackendController,
+    self_update_handle: SelfUpdateHandle,
+    status: UpdateStatusTracker,
+    state: Arc<StateStorage>,
 }
 
 impl UpdateManager {
@@ -168,20 +189,29 @@ impl UpdateManager {
         quit_notification: ServerQuitWatcher,
         api_client: Arc<ApiClient>,
         reboot_manager_handle: RebootManagerHandle,
+        backend_controller: BackendController,
+        self_update_handle: SelfUpdateHandle,
+        state: Arc<StateStorage>,
     ) -> (UpdateManagerQuitHandle, UpdateManagerHandle) {
         let (sender, receiver) = InProgressChannel::create();
+        let status = UpdateStatusTracker::default();
 
         let manager = Self {
             config,
             api_client,
             receiver,
             reboot_manager_handle,
+            backend_controller,
+            self_update_handle,
+            status: status.clone(),
+            state,
         };
 
         let task = tokio::spawn(manager.run(quit_notification));
 
         let handle = UpdateManagerHandle {
             sender: sender.clone(),
+            status,
         };
 
         let quit_handle = UpdateManagerQuitHandle {
@@ -223,33 +253,45 @@ impl UpdateManager {
     }
 
     pub async fn handle_message(&self, message: &UpdateManagerMessage) {
+        self.status.start();
         match message.clone() {
             UpdateManagerMessage::UpdateSoftware {
                 force_reboot,
                 reset_data,
                 software,
-            } => match self
-                .update_software(force_reboot, reset_data, software)
-                .await
-            {
-                Ok(()) => {
-                    info!("Software update finished");
-                }
-                Err(e) => {
-                    warn!("Software update failed. Error: {:?}", e);
+            } => {
+                let result = self
+                    .update_software(force_reboot, reset_data, software)
+                    .await;
+                METRICS.update_finished(software, result.is_ok());
+                match result {
+                    Ok(()) => {
+                        info!("Software update finished");
+                        self.status.finish(None);
+                    }
+                    Err(e) => {
+                        warn!("Software update failed. Error: {:?}", e);
+                        self.status.finish(Some(format!("{:#}", e)));
+                    }
                 }
-            },
+            }
             UpdateManagerMessage::RestartBackend { reset_data } => {
                 match self.restart_backend(reset_data).await {
                     Ok(()) => {
                         info!("Backend restart finished");
+                        self.status.finish(None);
                     }
                     Err(e) => {
                         warn!("Backend restart failed. Error: {:?}", e);
+                        self.status.finish(Some(format!("{:#}", e)));
                     }
                 }
             }
         }
+        let result = UpdateResult::new(self.status.status());
+        self.state
+            .modify(|s| s.persistent.last_update = Some(result))
+            .await;
     }
 
     pub async fn download_latest_info(
@@ -377,12 +419,29 @@ impl UpdateManager {
         .change_context(UpdateError::FileWritingFailed)?;
 
         if reset_data.reset_data {
+            self.status
+                .step(UpdateStep::ResetData, "Resetting backend data");
             self.reset_data(software).await?;
         }
 
-        REBOOT_ON_NEXT_CHECK.store(true, Ordering::Relaxed);
+        if software == SoftwareOptions::Manager
+            && self.config.manager_self_update()
+            && !force_reboot
+        {
+            self.status.step(
+                UpdateStep::ManagerRestart,
+                "Restarting manager using the new binary",
+            );
+            self.self_update_handle.request_restart();
+            return Ok(());
+        }
+
+        self.reboot_manager_handle
+            .schedule_reboot(RebootReason::SoftwareUpdate)
+            .await;
 
         if force_reboot {
+            self.status.step(UpdateStep::Reboot, "Rebooting now");
             self.reboot_manager_handle
                 .reboot_now()
                 .await
@@ -390,6 +449,8 @@ impl UpdateManager {
             info!("Rebooting now");
         } else {
             info!("Rebooting on next check");
+            self.status
+                .step(UpdateStep::Reboot, "Rebooting on next check");
         }
 
         Ok(())
@@ -409,6 +470,10 @@ impl UpdateManager {
                 "Downloading and decrypting software...\n{:#?}",
                 latest_version
             );
+            self.status.step(
+                UpdateStep::Download,
+                format!("Downloading {}", latest_version.name),
+            );
             self.download_and_decrypt_latest_software(&latest_version, software)
                 .await?;
             info!("Software is now downloaded and decrypted.");
@@ -419,6 +484,10 @@ impl UpdateManager {
         let latest_installed_version = self.read_latest_installed_build_info(software).await?;
         if latest_version != latest_installed_version {
             info!("Installing software.\n{:#?}", latest_version);
+            self.status.step(
+                UpdateStep::Install,
+                format!("Installing {}", latest_version.name),
+            );
             self.install_latest_software(&latest_version, force_reboot, reset_data, software)
                 .await?;
             info!("Software installation completed.");
@@ -578,19 +647,25 @@ impl UpdateManager {
         &self,
         reset_data: ResetDataQueryParam,
     ) -> Result<(), UpdateError> {
-        let backend_controller = BackendController::new(&self.config);
+        if !reset_data.reset_data {
+            return self
+                .backend_controller
+                .restart_backend(Some(&self.status))
+                .await
+                .change_context(UpdateError::RestartBackendFailed);
+        }
 
-        backend_controller
-            .stop_backend()
+        self.backend_controller
+            .stop_backend(Some(&self.status))
             .await
             .change_context(UpdateError::StopBackendFailed)?;
 
-        if reset_data.reset_data {
-            self.reset_data(SoftwareOptions::Backend).await?;
-        }
+        self.status
+            .step(UpdateStep::ResetData, "Resetting backend data");
+        self.reset_data(SoftwareOptions::Backend).await?;
 
-        backend_controller
-            .start_backend()
+        self.backend_controller
+            .start_backend(Some(&self.status))
             .await
             .change_context(UpdateError::StartBackendFailed)
     }
@@ -662,3 +737,111 @@ impl UpdateDirCreator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use manager_model::UpdateState;
+    use tokio::sync::broadcast;
+
+    use super::*;
+    use crate::{
+        config::load_test_config,
+        server::{
+            backend_controller::test_utils::FakeServiceController, reboot::RebootManager,
+            self_update::self_update_channel,
+        },
+    };
+
+    fn update_manager(
+        config: Config,
+        controller: Arc<FakeServiceController>,
+        quit_watcher: ServerQuitWatcher,
+    ) -> UpdateManager {
+        let config = Arc::new(config);
+        let api_client = Arc::new(ApiClient::new(&config).unwrap());
+        let state = Arc::new(StateStorage::new(&config));
+        let (_, reboot_manager_handle) = RebootManager::new_manager(
+            config.clone(),
+            api_client.clone(),
+            state.clone(),
+            quit_watcher,
+        );
+        let (_, receiver) = InProgressChannel::create();
+        UpdateManager {
+            backend_controller: BackendController::new(controller, &config),
+            config,
+            api_client,
+            receiver,
+            reboot_manager_handle,
+            self_update_handle: self_update_channel().0,
+            status: UpdateStatusTracker::default(),
+            state,
+        }
+    }
+
+    #[tokio::test]
+    async fn restart_backend_uses_controller_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_quit_handle, quit_watcher) = broadcast::channel(1);
+        let controller = Arc::new(FakeServiceController::new(true));
+        let manager = update_manager(
+            load_test_config(dir.path(), ""),
+            controller.clone(),
+            quit_watcher,
+        );
+
+        let reset_data = ResetDataQueryParam { reset_data: false };
+        manager
+            .handle_message(&UpdateManagerMessage::RestartBackend { reset_data })
+            .await;
+
+        assert_eq!(controller.actions(), ["restart"]);
+        assert!(controller.is_running());
+        let status = manager.status.status();
+        assert_eq!(status.state, UpdateState::Finished);
+        assert!(status
+            .steps
+            .iter()
+            .any(|step| step.message == "Backend stopped"));
+    }
+
+    #[tokio::test]
+    async fn restart_backend_with_data_reset_stops_and_starts_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        let data_dir = dir.path().join("data");
+        std::fs::create_dir(&data_dir).unwrap();
+        let config = load_test_config(
+            dir.path(),
+            &format!(
+                r#"
+[software_update_provider]
+manager_base_url = "http://127.0.0.1:5001"
+manager_install_location = "{dir}/app-manager"
+backend_install_location = "{dir}/app-backend"
+backend_data_reset_dir = "{data_dir}"
+"#,
+                dir = dir.path().display(),
+                data_dir = data_dir.display(),
+            ),
+        );
+        let (_quit_handle, quit_watcher) = broadcast::channel(1);
+        let controller = Arc::new(FakeServiceController::new(false));
+        let manager = update_manager(config, controller.clone(), quit_watcher);
+
+        let reset_data = ResetDataQueryParam { reset_data: true };
+        manager
+            .handle_message(&UpdateManagerMessage::RestartBackend { reset_data })
+            .await;
+
+        assert_eq!(controller.actions(), ["stop", "start"]);
+        assert!(controller.is_running());
+        assert!(!data_dir.exists());
+        assert!(dir.path().join("data-old").is_dir());
+        let status = manager.status.status();
+        assert_eq!(status.state, UpdateState::Finished);
+        assert!(status
+            .steps
+            .iter()
+            .any(|step| step.message == "Backend was not running"));
+    }
+}
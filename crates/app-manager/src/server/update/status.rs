@@ -0,0 +1,74 @@
+//! Track software update and backend restart progress
+
+use std::sync::{Arc, Mutex};
+
+use manager_model::{UpdateState, UpdateStatus, UpdateStep, UpdateStepInfo};
+use tokio::time::Instant;
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    status: UpdateStatus,
+    started: Option<Instant>,
+}
+
+/// Shared update status. Only the latest update is stored.
+#[derive(Debug, Clone, Default)]
+pub struct UpdateStatusTracker {
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl UpdateStatusTracker {
+    /// Clears steps of the previous update.
+    pub fn start(&self) {
+        self.modify(|state| {
+            state.status = UpdateStatus {
+                state: UpdateState::InProgress,
+                ..Default::default()
+            };
+            state.started = Some(Instant::now());
+        });
+    }
+
+    pub fn step(&self, step: UpdateStep, message: impl Into<String>) {
+        let message = message.into();
+        self.modify(|state| {
+            let elapsed_millis = state
+                .started
+                .map(|started| started.elapsed().as_millis())
+                .unwrap_or_default()
+                .try_into()
+                .unwrap_or(u64::MAX);
+            state.status.steps.push(UpdateStepInfo {
+                step,
+                message,
+                elapsed_millis,
+            });
+        });
+    }
+
+    /// Error is None if the update succeeded.
+    pub fn finish(&self, error: Option<String>) {
+        self.modify(|state| {
+            state.status.state = if error.is_some() {
+                UpdateState::Failed
+            } else {
+                UpdateState::Finished
+            };
+            state.status.error = error;
+        });
+    }
+
+    pub fn status(&self) -> UpdateStatus {
+        match self.state.lock() {
+            Ok(state) => state.status.clone(),
+            Err(e) => e.into_inner().status.clone(),
+        }
+    }
+
+    fn modify(&self, action: impl FnOnce(&mut TrackerState)) {
+        match self.state.lock() {
+            Ok(mut state) => action(&mut state),
+            Err(e) => action(&mut e.into_inner()),
+        }
+    }
+}
@@ -9,23 +9,32 @@
 pub use manager_api_client::apis::{
     configuration::{ApiKey, Configuration},
     manager_api::{
-        GetEncryptionKeyError, GetSoftwareInfoError, GetSystemInfoAllError, GetSystemInfoError,
-        PostRequestBuildSoftwareError, PostRequestRestartOrResetBackendError,
-        PostRequestSoftwareUpdateError,
+        GetBackendStatusError, GetEncryptionKeyError, GetLogsError, GetRebootStatusError,
+        GetSoftwareInfoError, GetSystemInfoAllError, GetSystemInfoError, GetUpdateStatusError,
+        PostAcquireRebootLeaseError, PostCancelRebootError, PostRebootNowError,
+        PostReleaseRebootLeaseError, PostReloadConfigError, PostRequestBuildSoftwareError,
+        PostRequestRestartOrResetBackendError, PostRequestSoftwareUpdateError,
+        PostScheduleRebootError,
     },
     Error,
 };
+pub use manager_api_client::manual_additions::LogStream;
 use manager_api_client::{
     apis::manager_api::{
-        get_encryption_key, get_software_info, get_system_info_all, post_request_build_software,
-        post_request_restart_or_reset_backend, post_request_software_update,
-        GetLatestSoftwareError,
+        get_backend_status, get_encryption_key, get_reboot_status, get_software_info,
+        get_system_info_all, get_update_status, post_acquire_reboot_lease, post_cancel_reboot,
+        post_reboot_now, post_release_reboot_lease, post_reload_config,
+        post_request_build_software, post_request_restart_or_reset_backend,
+        post_request_software_update, post_schedule_reboot, GetLatestSoftwareError,
     },
-    manual_additions::get_latest_software_fixed,
+    manual_additions::{get_latest_software_fixed, get_logs_stream},
 };
 use manager_model::{
-    BuildInfo, CommandOutput, DataEncryptionKey, ResetDataQueryParam, SoftwareInfo,
-    SoftwareOptions, SystemInfo, SystemInfoList,
+    BackendStatus, BuildInfo, CancelRebootResult, CommandOutput, CommandStatus, ConfigReloadReport,
+    DataEncryptionKey, DiskUsage, LoadAverage, LogsQueryParam, MemoryUsage, ProcessInfo,
+    RebootAttempt, RebootLease, RebootReason, RebootStatus, ResetDataQueryParam, SoftwareInfo,
+    SoftwareOptions, SystemInfo, SystemInfoList, SystemInfoSectionsQueryParam, SystemMetrics,
+    UpdateState, UpdateStatus, UpdateStep, UpdateStepInfo,
 };
 
 pub struct ManagerApi;
@@ -99,26 +108,14 @@ impl ManagerApi {
 
     pub async fn system_info_all(
         configuration: &Configuration,
+        sections: &SystemInfoSectionsQueryParam,
     ) -> Result<SystemInfoList, Error<GetSystemInfoAllError>> {
-        let system_info = get_system_info_all(configuration).await?;
+        let system_info = get_system_info_all(configuration, sections.sections.as_deref()).await?;
 
         let info_vec = system_info
             .info
             .into_iter()
-            .map(|info| {
-                let cmd_vec = info
-                    .info
-                    .into_iter()
-                    .map(|info| CommandOutput {
-                        name: info.name,
-                        output: info.output,
-                    })
-                    .collect::<Vec<CommandOutput>>();
-                SystemInfo {
-                    name: info.name,
-                    info: cmd_vec,
-                }
-            })
+            .map(convert_system_info)
             .collect::<Vec<SystemInfo>>();
 
         Ok(SystemInfoList { info: info_vec })
@@ -126,23 +123,31 @@ impl ManagerApi {
 
     pub async fn system_info(
         configuration: &Configuration,
+        sections: &SystemInfoSectionsQueryParam,
     ) -> Result<SystemInfo, Error<GetSystemInfoError>> {
-        let system_info =
-            manager_api_client::apis::manager_api::get_system_info(configuration).await?;
+        let system_info = manager_api_client::apis::manager_api::get_system_info(
+            configuration,
+            sections.sections.as_deref(),
+        )
+        .await?;
 
-        let info_vec = system_info
-            .info
-            .into_iter()
-            .map(|info| CommandOutput {
-                name: info.name,
-                output: info.output,
-            })
-            .collect::<Vec<CommandOutput>>();
+        Ok(convert_system_info(system_info))
+    }
 
-        Ok(SystemInfo {
-            name: system_info.name,
-            info: info_vec,
-        })
+    /// If follow is true, the stream continues until the connection
+    /// is closed.
+    pub async fn logs(
+        configuration: &Configuration,
+        query: &LogsQueryParam,
+    ) -> Result<LogStream, Error<GetLogsError>> {
+        get_logs_stream(
+            configuration,
+            &query.service,
+            Some(query.follow),
+            query.priority.as_deref(),
+            query.since.as_deref(),
+        )
+        .await
     }
 
     pub async fn request_update_software(
@@ -192,4 +197,225 @@ impl ManagerApi {
     ) -> Result<(), Error<PostRequestRestartOrResetBackendError>> {
         post_request_restart_or_reset_backend(configuration, reset_data.reset_data).await
     }
+
+    pub async fn backend_status(
+        configuration: &Configuration,
+    ) -> Result<BackendStatus, Error<GetBackendStatusError>> {
+        let status = get_backend_status(configuration).await?;
+
+        Ok(BackendStatus {
+            supervised: status.supervised,
+            running: status.running,
+            pid: status.pid.and_then(|pid| pid.try_into().ok()),
+            uptime_seconds: status.uptime_seconds.map(to_u64),
+            last_exit_code: status.last_exit_code,
+            restart_count: status.restart_count.try_into().unwrap_or_default(),
+            systemd_unit: status.systemd_unit,
+            active_state: status.active_state,
+            sub_state: status.sub_state,
+        })
+    }
+
+    pub async fn update_status(
+        configuration: &Configuration,
+    ) -> Result<UpdateStatus, Error<GetUpdateStatusError>> {
+        let status = get_update_status(configuration).await?;
+
+        let steps = status
+            .steps
+            .into_iter()
+            .map(|info| UpdateStepInfo {
+                step: match info.step {
+                    manager_api_client::models::UpdateStep::Download => UpdateStep::Download,
+                    manager_api_client::models::UpdateStep::Install => UpdateStep::Install,
+                    manager_api_client::models::UpdateStep::ResetData => UpdateStep::ResetData,
+                    manager_api_client::models::UpdateStep::Reboot => UpdateStep::Reboot,
+                    manager_api_client::models::UpdateStep::BackendDrain => {
+                        UpdateStep::BackendDrain
+                    }
+                    manager_api_client::models::UpdateStep::BackendStop => UpdateStep::BackendStop,
+                    manager_api_client::models::UpdateStep::BackendKill => UpdateStep::BackendKill,
+                    manager_api_client::models::UpdateStep::BackendStart => {
+                        UpdateStep::BackendStart
+                    }
+                    manager_api_client::models::UpdateStep::ManagerRestart => {
+                        UpdateStep::ManagerRestart
+                    }
+                },
+                message: info.message,
+                elapsed_millis: to_u64(info.elapsed_millis),
+            })
+            .collect();
+
+        Ok(UpdateStatus {
+            state: match status.state {
+                manager_api_client::models::UpdateState::Idle => UpdateState::Idle,
+                manager_api_client::models::UpdateState::InProgress => UpdateState::InProgress,
+                manager_api_client::models::UpdateState::Finished => UpdateState::Finished,
+                manager_api_client::models::UpdateState::Failed => UpdateState::Failed,
+            },
+            steps,
+            error: status.error,
+        })
+    }
+
+    pub async fn reboot_status(
+        configuration: &Configuration,
+    ) -> Result<RebootStatus, Error<GetRebootStatusError>> {
+        let status = get_reboot_status(configuration).await?;
+
+        Ok(RebootStatus {
+            pending: status.pending,
+            reason: status.reason.map(|reason| match reason {
+                manager_api_client::models::RebootReason::RebootRequiredFile => {
+                    RebootReason::RebootRequiredFile
+                }
+                manager_api_client::models::RebootReason::SoftwareUpdate => {
+                    RebootReason::SoftwareUpdate
+                }
+                manager_api_client::models::RebootReason::Scheduled => RebootReason::Scheduled,
+            }),
+            next_check_time: status.next_check_time,
+            last_attempt: status.last_attempt.map(|attempt| RebootAttempt {
+                time: attempt.time,
+                success: attempt.success,
+                error: attempt.error,
+            }),
+        })
+    }
+
+    pub async fn schedule_reboot(
+        configuration: &Configuration,
+    ) -> Result<(), Error<PostScheduleRebootError>> {
+        post_schedule_reboot(configuration).await
+    }
+
+    pub async fn reboot_now(
+        configuration: &Configuration,
+    ) -> Result<(), Error<PostRebootNowError>> {
+        post_reboot_now(configuration).await
+    }
+
+    pub async fn cancel_reboot(
+        configuration: &Configuration,
+    ) -> Result<CancelRebootResult, Error<PostCancelRebootError>> {
+        let result = post_cancel_reboot(configuration).await?;
+
+        Ok(CancelRebootResult {
+            was_pending: result.was_pending,
+        })
+    }
+
+    pub async fn acquire_reboot_lease(
+        configuration: &Configuration,
+        name: &str,
+    ) -> Result<RebootLease, Error<PostAcquireRebootLeaseError>> {
+        let lease = post_acquire_reboot_lease(configuration, name).await?;
+
+        Ok(RebootLease {
+            granted: lease.granted,
+            holders: lease.holders,
+        })
+    }
+
+    pub async fn release_reboot_lease(
+        configuration: &Configuration,
+        name: &str,
+    ) -> Result<(), Error<PostReleaseRebootLeaseError>> {
+        post_release_reboot_lease(configuration, name).await
+    }
+
+    pub async fn reload_config(
+        configuration: &Configuration,
+    ) -> Result<ConfigReloadReport, Error<PostReloadConfigError>> {
+        let report = post_reload_config(configuration).await?;
+
+        Ok(ConfigReloadReport {
+            reloaded: report.reloaded,
+            restart_required: report.restart_required,
+        })
+    }
+}
+
+fn convert_system_info(info: manager_api_client::models::SystemInfo) -> SystemInfo {
+    let cmd_vec = info
+        .info
+        .into_iter()
+        .map(|info| CommandOutput {
+            name: info.name,
+            output: info.output,
+            status: match info.status {
+                None | Some(manager_api_client::models::CommandStatus::Success) => {
+                    CommandStatus::Success
+                }
+                Some(manager_api_client::models::CommandStatus::Failed) => CommandStatus::Failed,
+                Some(manager_api_client::models::CommandStatus::StartFailed) => {
+                    CommandStatus::StartFailed
+                }
+                Some(manager_api_client::models::CommandStatus::Timeout) => CommandStatus::Timeout,
+            },
+            exit_code: info.exit_code,
+            stderr: info.stderr.unwrap_or_default(),
+        })
+        .collect::<Vec<CommandOutput>>();
+
+    SystemInfo {
+        name: info.name,
+        info: cmd_vec,
+        metrics: info.metrics.map(|metrics| convert_system_metrics(*metrics)),
+        error: info.error,
+        duration_millis: to_u64(info.duration_millis.unwrap_or_default()),
+    }
+}
+
+fn convert_system_metrics(metrics: manager_api_client::models::SystemMetrics) -> SystemMetrics {
+    let disks = metrics
+        .disks
+        .into_iter()
+        .map(|disk| DiskUsage {
+            mount_point: disk.mount_point,
+            file_system: disk.file_system,
+            total_bytes: to_u64(disk.total_bytes),
+            used_bytes: to_u64(disk.used_bytes),
+            available_bytes: to_u64(disk.available_bytes),
+            total_inodes: to_u64(disk.total_inodes),
+            used_inodes: to_u64(disk.used_inodes),
+            available_inodes: to_u64(disk.available_inodes),
+        })
+        .collect::<Vec<DiskUsage>>();
+
+    let top_processes = metrics
+        .top_processes
+        .into_iter()
+        .map(|process| ProcessInfo {
+            pid: process.pid.try_into().unwrap_or_default(),
+            name: process.name,
+            cpu_percent: process.cpu_percent,
+            memory_bytes: to_u64(process.memory_bytes),
+        })
+        .collect::<Vec<ProcessInfo>>();
+
+    SystemMetrics {
+        disks,
+        memory: MemoryUsage {
+            total_bytes: to_u64(metrics.memory.total_bytes),
+            used_bytes: to_u64(metrics.memory.used_bytes),
+            available_bytes: to_u64(metrics.memory.available_bytes),
+            swap_total_bytes: to_u64(metrics.memory.swap_total_bytes),
+            swap_used_bytes: to_u64(metrics.memory.swap_used_bytes),
+        },
+        load_average: LoadAverage {
+            one_minute: metrics.load_average.one_minute,
+            five_minutes: metrics.load_average.five_minutes,
+            fifteen_minutes: metrics.load_average.fifteen_minutes,
+        },
+        uptime_seconds: to_u64(metrics.uptime_seconds),
+        cpu_count: metrics.cpu_count.try_into().unwrap_or_default(),
+        top_processes,
+    }
+}
+
+/// Generated client uses signed integers for unsigned values.
+fn to_u64(value: i64) -> u64 {
+    value.try_into().unwrap_or_default()
 }
@@ -1,4 +1,4 @@
-use crate::{apis::{configuration, Error, manager_api::GetLatestSoftwareError, ResponseContent}, models::{SoftwareOptions, DownloadType}};
+use crate::{apis::{configuration, Error, manager_api::{GetLatestSoftwareError, GetLogsError}, ResponseContent}, models::{SoftwareOptions, DownloadType}};
 
 
 
@@ -43,3 +43,65 @@ pub async fn get_latest_software_fixed(configuration: &configuration::Configurat
         Err(Error::ResponseError(local_var_error))
     }
 }
+
+/// Chunked response of get_logs request
+pub struct LogStream {
+    response: reqwest::Response,
+}
+
+impl LogStream {
+    /// Returns None when the server closes the response.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, Error<GetLogsError>> {
+        let chunk = self.response.chunk().await?;
+        Ok(chunk.map(|chunk| chunk.to_vec()))
+    }
+}
+
+/// Get journal logs of a service
+///
+/// Returns the response without reading the body, so that logs can be
+/// streamed when follow is true.
+pub async fn get_logs_stream(configuration: &configuration::Configuration, service: &str, follow: Option<bool>, priority: Option<&str>, since: Option<&str>) -> Result<LogStream, Error<GetLogsError>> {
+    let local_var_configuration = configuration;
+
+    let local_var_client = &local_var_configuration.client;
+
+    let local_var_uri_str = format!("{}/manager_api/logs", local_var_configuration.base_path);
+    let mut local_var_req_builder = local_var_client.request(reqwest::Method::GET, local_var_uri_str.as_str());
+
+    local_var_req_builder = local_var_req_builder.query(&[("service", &service.to_string())]);
+    if let Some(ref local_var_str) = follow {
+        local_var_req_builder = local_var_req_builder.query(&[("follow", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_str) = priority {
+        local_var_req_builder = local_var_req_builder.query(&[("priority", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_str) = since {
+        local_var_req_builder = local_var_req_builder.query(&[("since", &local_var_str.to_string())]);
+    }
+    if let Some(ref local_var_user_agent) = local_var_configuration.user_agent {
+        local_var_req_builder = local_var_req_builder.header(reqwest::header::USER_AGENT, local_var_user_agent.clone());
+    }
+    if let Some(ref local_var_apikey) = local_var_configuration.api_key {
+        let local_var_key = local_var_apikey.key.clone();
+        let local_var_value = match local_var_apikey.prefix {
+            Some(ref local_var_prefix) => format!("{} {}", local_var_prefix, local_var_key),
+            None => local_var_key,
+        };
+        local_var_req_builder = local_var_req_builder.header("x-api-key", local_var_value);
+    };
+
+    let local_var_req = local_var_req_builder.build()?;
+    let local_var_resp = local_var_client.execute(local_var_req).await?;
+
+    let local_var_status = local_var_resp.status();
+
+    if !local_var_status.is_client_error() && !local_var_status.is_server_error() {
+        Ok(LogStream { response: local_var_resp })
+    } else {
+        let status_string = local_var_status.to_string();
+        let local_var_entity: Option<GetLogsError> = serde_json::from_str(&status_string).ok();
+        let local_var_error = ResponseContent { status: local_var_status, content: status_string, entity: local_var_entity };
+        Err(Error::ResponseError(local_var_error))
+    }
+}
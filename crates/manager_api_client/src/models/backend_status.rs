@@ -0,0 +1,59 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// BackendStatus : Backend process status. Process related values are available only when the backend is supervised by the manager or controlled using systemd.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackendStatus {
+    /// Systemd unit ActiveState. For example "active" or "failed".
+    #[serde(rename = "active_state", skip_serializing_if = "Option::is_none")]
+    pub active_state: Option<String>,
+    /// Exit code of the previous backend process. Missing if the process has not exited or it was terminated by a signal.
+    #[serde(rename = "last_exit_code", skip_serializing_if = "Option::is_none")]
+    pub last_exit_code: Option<i32>,
+    #[serde(rename = "pid", skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i32>,
+    /// Restarts after unexpected backend exits.
+    #[serde(rename = "restart_count")]
+    pub restart_count: i32,
+    #[serde(rename = "running")]
+    pub running: bool,
+    /// Systemd unit SubState. For example "running" or "exited".
+    #[serde(rename = "sub_state", skip_serializing_if = "Option::is_none")]
+    pub sub_state: Option<String>,
+    /// Backend is started and stopped by the manager instead of scripts.
+    #[serde(rename = "supervised")]
+    pub supervised: bool,
+    /// Backend is controlled using this systemd unit.
+    #[serde(rename = "systemd_unit", skip_serializing_if = "Option::is_none")]
+    pub systemd_unit: Option<String>,
+    #[serde(rename = "uptime_seconds", skip_serializing_if = "Option::is_none")]
+    pub uptime_seconds: Option<i64>,
+}
+
+impl BackendStatus {
+    /// Backend process status. Process related values are available only when the backend is supervised by the manager or controlled using systemd.
+    pub fn new(restart_count: i32, running: bool, supervised: bool) -> BackendStatus {
+        BackendStatus {
+            active_state: None,
+            last_exit_code: None,
+            pid: None,
+            restart_count,
+            running,
+            sub_state: None,
+            supervised,
+            systemd_unit: None,
+            uptime_seconds: None,
+        }
+    }
+}
+
@@ -0,0 +1,30 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// CancelRebootResult : Result of the cancel reboot request.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CancelRebootResult {
+    /// True if a reboot was pending and it is now cancelled.
+    #[serde(rename = "was_pending")]
+    pub was_pending: bool,
+}
+
+impl CancelRebootResult {
+    /// Result of the cancel reboot request.
+    pub fn new(was_pending: bool) -> CancelRebootResult {
+        CancelRebootResult {
+            was_pending,
+        }
+    }
+}
+
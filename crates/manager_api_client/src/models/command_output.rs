@@ -13,17 +13,29 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct CommandOutput {
+    /// Missing if the command did not start or it was terminated by a signal.
+    #[serde(rename = "exit_code", skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
     #[serde(rename = "name")]
     pub name: String,
+    /// Standard output
     #[serde(rename = "output")]
     pub output: String,
+    #[serde(rename = "status", skip_serializing_if = "Option::is_none")]
+    pub status: Option<models::CommandStatus>,
+    /// Standard error or error message if the command did not start.
+    #[serde(rename = "stderr", skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
 }
 
 impl CommandOutput {
     pub fn new(name: String, output: String) -> CommandOutput {
         CommandOutput {
+            exit_code: None,
             name,
             output,
+            status: None,
+            stderr: None,
         }
     }
 }
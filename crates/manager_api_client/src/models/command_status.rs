@@ -0,0 +1,44 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum CommandStatus {
+    #[serde(rename = "Success")]
+    Success,
+    #[serde(rename = "Failed")]
+    Failed,
+    #[serde(rename = "StartFailed")]
+    StartFailed,
+    #[serde(rename = "Timeout")]
+    Timeout,
+
+}
+
+impl std::fmt::Display for CommandStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Success => write!(f, "Success"),
+            Self::Failed => write!(f, "Failed"),
+            Self::StartFailed => write!(f, "StartFailed"),
+            Self::Timeout => write!(f, "Timeout"),
+        }
+    }
+}
+
+impl Default for CommandStatus {
+    fn default() -> CommandStatus {
+        Self::Success
+    }
+}
+
@@ -0,0 +1,32 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConfigReloadReport {
+    /// Config file fields which changed and are now in use.
+    #[serde(rename = "reloaded")]
+    pub reloaded: Vec<String>,
+    /// Config file fields which changed but require manager restart.
+    #[serde(rename = "restart_required")]
+    pub restart_required: Vec<String>,
+}
+
+impl ConfigReloadReport {
+    pub fn new(reloaded: Vec<String>, restart_required: Vec<String>) -> ConfigReloadReport {
+        ConfigReloadReport {
+            reloaded,
+            restart_required,
+        }
+    }
+}
+
@@ -0,0 +1,49 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    /// Space available for unprivileged users.
+    #[serde(rename = "available_bytes")]
+    pub available_bytes: i64,
+    #[serde(rename = "available_inodes")]
+    pub available_inodes: i64,
+    #[serde(rename = "file_system")]
+    pub file_system: String,
+    #[serde(rename = "mount_point")]
+    pub mount_point: String,
+    #[serde(rename = "total_bytes")]
+    pub total_bytes: i64,
+    #[serde(rename = "total_inodes")]
+    pub total_inodes: i64,
+    #[serde(rename = "used_bytes")]
+    pub used_bytes: i64,
+    #[serde(rename = "used_inodes")]
+    pub used_inodes: i64,
+}
+
+impl DiskUsage {
+    pub fn new(available_bytes: i64, available_inodes: i64, file_system: String, mount_point: String, total_bytes: i64, total_inodes: i64, used_bytes: i64, used_inodes: i64) -> DiskUsage {
+        DiskUsage {
+            available_bytes,
+            available_inodes,
+            file_system,
+            mount_point,
+            total_bytes,
+            total_inodes,
+            used_bytes,
+            used_inodes,
+        }
+    }
+}
+
@@ -0,0 +1,33 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LoadAverage {
+    #[serde(rename = "fifteen_minutes")]
+    pub fifteen_minutes: f64,
+    #[serde(rename = "five_minutes")]
+    pub five_minutes: f64,
+    #[serde(rename = "one_minute")]
+    pub one_minute: f64,
+}
+
+impl LoadAverage {
+    pub fn new(fifteen_minutes: f64, five_minutes: f64, one_minute: f64) -> LoadAverage {
+        LoadAverage {
+            fifteen_minutes,
+            five_minutes,
+            one_minute,
+        }
+    }
+}
+
@@ -0,0 +1,42 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// LogsQueryParam : Journal log stream parameters. Missing optional values are read from the service's config.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogsQueryParam {
+    /// Keep the response open and stream new log lines.
+    #[serde(rename = "follow", skip_serializing_if = "Option::is_none")]
+    pub follow: Option<bool>,
+    /// Journal priority filter. For example "warning" or "err..warning".
+    #[serde(rename = "priority", skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    /// Service name from system info log_services config.
+    #[serde(rename = "service")]
+    pub service: String,
+    /// Show logs since this time. Uses journalctl time format.
+    #[serde(rename = "since", skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+}
+
+impl LogsQueryParam {
+    /// Journal log stream parameters. Missing optional values are read from the service's config.
+    pub fn new(service: String) -> LogsQueryParam {
+        LogsQueryParam {
+            follow: None,
+            priority: None,
+            service,
+            since: None,
+        }
+    }
+}
+
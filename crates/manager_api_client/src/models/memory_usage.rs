@@ -0,0 +1,39 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    #[serde(rename = "available_bytes")]
+    pub available_bytes: i64,
+    #[serde(rename = "swap_total_bytes")]
+    pub swap_total_bytes: i64,
+    #[serde(rename = "swap_used_bytes")]
+    pub swap_used_bytes: i64,
+    #[serde(rename = "total_bytes")]
+    pub total_bytes: i64,
+    #[serde(rename = "used_bytes")]
+    pub used_bytes: i64,
+}
+
+impl MemoryUsage {
+    pub fn new(available_bytes: i64, swap_total_bytes: i64, swap_used_bytes: i64, total_bytes: i64, used_bytes: i64) -> MemoryUsage {
+        MemoryUsage {
+            available_bytes,
+            swap_total_bytes,
+            swap_used_bytes,
+            total_bytes,
+            used_bytes,
+        }
+    }
+}
+
@@ -1,15 +1,41 @@
+pub mod backend_status;
+pub use self::backend_status::BackendStatus;
 pub mod build_info;
 pub use self::build_info::BuildInfo;
+pub mod cancel_reboot_result;
+pub use self::cancel_reboot_result::CancelRebootResult;
 pub mod command_output;
 pub use self::command_output::CommandOutput;
+pub mod command_status;
+pub use self::command_status::CommandStatus;
+pub mod config_reload_report;
+pub use self::config_reload_report::ConfigReloadReport;
 pub mod data_encryption_key;
 pub use self::data_encryption_key::DataEncryptionKey;
+pub mod disk_usage;
+pub use self::disk_usage::DiskUsage;
 pub mod download_type;
 pub use self::download_type::DownloadType;
 pub mod download_type_query_param;
 pub use self::download_type_query_param::DownloadTypeQueryParam;
+pub mod load_average;
+pub use self::load_average::LoadAverage;
+pub mod logs_query_param;
+pub use self::logs_query_param::LogsQueryParam;
+pub mod memory_usage;
+pub use self::memory_usage::MemoryUsage;
+pub mod process_info;
+pub use self::process_info::ProcessInfo;
+pub mod reboot_attempt;
+pub use self::reboot_attempt::RebootAttempt;
+pub mod reboot_lease;
+pub use self::reboot_lease::RebootLease;
 pub mod reboot_query_param;
 pub use self::reboot_query_param::RebootQueryParam;
+pub mod reboot_reason;
+pub use self::reboot_reason::RebootReason;
+pub mod reboot_status;
+pub use self::reboot_status::RebootStatus;
 pub mod reset_data_query_param;
 pub use self::reset_data_query_param::ResetDataQueryParam;
 pub mod server_name_text;
@@ -24,3 +50,15 @@ pub mod system_info;
 pub use self::system_info::SystemInfo;
 pub mod system_info_list;
 pub use self::system_info_list::SystemInfoList;
+pub mod system_info_sections_query_param;
+pub use self::system_info_sections_query_param::SystemInfoSectionsQueryParam;
+pub mod system_metrics;
+pub use self::system_metrics::SystemMetrics;
+pub mod update_state;
+pub use self::update_state::UpdateState;
+pub mod update_status;
+pub use self::update_status::UpdateStatus;
+pub mod update_step;
+pub use self::update_step::UpdateStep;
+pub mod update_step_info;
+pub use self::update_step_info::UpdateStepInfo;
@@ -0,0 +1,38 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    /// CPU usage during a short sample period. One fully used CPU core is 100%.
+    #[serde(rename = "cpu_percent")]
+    pub cpu_percent: f64,
+    /// Resident set size
+    #[serde(rename = "memory_bytes")]
+    pub memory_bytes: i64,
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "pid")]
+    pub pid: i32,
+}
+
+impl ProcessInfo {
+    pub fn new(cpu_percent: f64, memory_bytes: i64, name: String, pid: i32) -> ProcessInfo {
+        ProcessInfo {
+            cpu_percent,
+            memory_bytes,
+            name,
+            pid,
+        }
+    }
+}
+
@@ -0,0 +1,35 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RebootAttempt {
+    /// Error message if the reboot failed.
+    #[serde(rename = "error", skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(rename = "success")]
+    pub success: bool,
+    /// Unix timestamp in seconds.
+    #[serde(rename = "time")]
+    pub time: i64,
+}
+
+impl RebootAttempt {
+    pub fn new(success: bool, time: i64) -> RebootAttempt {
+        RebootAttempt {
+            error: None,
+            success,
+            time,
+        }
+    }
+}
+
@@ -0,0 +1,34 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// RebootLease : Reboot coordinator response to a reboot lease request.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RebootLease {
+    /// Server can reboot now.
+    #[serde(rename = "granted")]
+    pub granted: bool,
+    /// Names of the servers which hold a reboot lease.
+    #[serde(rename = "holders")]
+    pub holders: Vec<String>,
+}
+
+impl RebootLease {
+    /// Reboot coordinator response to a reboot lease request.
+    pub fn new(granted: bool, holders: Vec<String>) -> RebootLease {
+        RebootLease {
+            granted,
+            holders,
+        }
+    }
+}
+
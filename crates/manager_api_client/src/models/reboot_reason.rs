@@ -0,0 +1,41 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum RebootReason {
+    #[serde(rename = "RebootRequiredFile")]
+    RebootRequiredFile,
+    #[serde(rename = "SoftwareUpdate")]
+    SoftwareUpdate,
+    #[serde(rename = "Scheduled")]
+    Scheduled,
+
+}
+
+impl std::fmt::Display for RebootReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::RebootRequiredFile => write!(f, "RebootRequiredFile"),
+            Self::SoftwareUpdate => write!(f, "SoftwareUpdate"),
+            Self::Scheduled => write!(f, "Scheduled"),
+        }
+    }
+}
+
+impl Default for RebootReason {
+    fn default() -> RebootReason {
+        Self::RebootRequiredFile
+    }
+}
+
@@ -0,0 +1,40 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// RebootStatus : Automatic reboot status.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RebootStatus {
+    #[serde(rename = "last_attempt", skip_serializing_if = "Option::is_none")]
+    pub last_attempt: Option<Box<models::RebootAttempt>>,
+    /// Unix timestamp in seconds. Missing if automatic reboots are disabled.
+    #[serde(rename = "next_check_time", skip_serializing_if = "Option::is_none")]
+    pub next_check_time: Option<i64>,
+    /// System will be rebooted at the next reboot check.
+    #[serde(rename = "pending")]
+    pub pending: bool,
+    #[serde(rename = "reason", skip_serializing_if = "Option::is_none")]
+    pub reason: Option<models::RebootReason>,
+}
+
+impl RebootStatus {
+    /// Automatic reboot status.
+    pub fn new(pending: bool) -> RebootStatus {
+        RebootStatus {
+            last_attempt: None,
+            next_check_time: None,
+            pending,
+            reason: None,
+        }
+    }
+}
+
@@ -13,8 +13,17 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SystemInfo {
+    /// Time used for getting the info in milliseconds. For remote managers this includes the API request.
+    #[serde(rename = "duration_millis", skip_serializing_if = "Option::is_none")]
+    pub duration_millis: Option<i64>,
+    /// Error message if getting system info failed. For example remote manager might not be available.
+    #[serde(rename = "error", skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
     #[serde(rename = "info")]
     pub info: Vec<models::CommandOutput>,
+    /// Missing if the manager does not support system metrics.
+    #[serde(rename = "metrics", skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<Box<models::SystemMetrics>>,
     #[serde(rename = "name")]
     pub name: String,
 }
@@ -22,7 +31,10 @@ pub struct SystemInfo {
 impl SystemInfo {
     pub fn new(info: Vec<models::CommandOutput>, name: String) -> SystemInfo {
         SystemInfo {
+            duration_millis: None,
+            error: None,
             info,
+            metrics: None,
             name,
         }
     }
@@ -0,0 +1,28 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SystemInfoSectionsQueryParam {
+    /// Comma separated list of sections. Possible values are commands, custom_commands, logs and metrics. All sections are returned by default.
+    #[serde(rename = "sections", skip_serializing_if = "Option::is_none")]
+    pub sections: Option<String>,
+}
+
+impl SystemInfoSectionsQueryParam {
+    pub fn new() -> SystemInfoSectionsQueryParam {
+        SystemInfoSectionsQueryParam {
+            sections: None,
+        }
+    }
+}
+
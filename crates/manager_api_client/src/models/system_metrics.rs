@@ -0,0 +1,45 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// SystemMetrics : System metrics in machine readable format.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SystemMetrics {
+    #[serde(rename = "cpu_count")]
+    pub cpu_count: i32,
+    #[serde(rename = "disks")]
+    pub disks: Vec<models::DiskUsage>,
+    #[serde(rename = "load_average")]
+    pub load_average: Box<models::LoadAverage>,
+    #[serde(rename = "memory")]
+    pub memory: Box<models::MemoryUsage>,
+    /// Processes sorted by CPU usage.
+    #[serde(rename = "top_processes")]
+    pub top_processes: Vec<models::ProcessInfo>,
+    #[serde(rename = "uptime_seconds")]
+    pub uptime_seconds: i64,
+}
+
+impl SystemMetrics {
+    /// System metrics in machine readable format.
+    pub fn new(cpu_count: i32, disks: Vec<models::DiskUsage>, load_average: Box<models::LoadAverage>, memory: Box<models::MemoryUsage>, top_processes: Vec<models::ProcessInfo>, uptime_seconds: i64) -> SystemMetrics {
+        SystemMetrics {
+            cpu_count,
+            disks,
+            load_average,
+            memory,
+            top_processes,
+            uptime_seconds,
+        }
+    }
+}
+
@@ -0,0 +1,44 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum UpdateState {
+    #[serde(rename = "Idle")]
+    Idle,
+    #[serde(rename = "InProgress")]
+    InProgress,
+    #[serde(rename = "Finished")]
+    Finished,
+    #[serde(rename = "Failed")]
+    Failed,
+
+}
+
+impl std::fmt::Display for UpdateState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Idle => write!(f, "Idle"),
+            Self::InProgress => write!(f, "InProgress"),
+            Self::Finished => write!(f, "Finished"),
+            Self::Failed => write!(f, "Failed"),
+        }
+    }
+}
+
+impl Default for UpdateState {
+    fn default() -> UpdateState {
+        Self::Idle
+    }
+}
+
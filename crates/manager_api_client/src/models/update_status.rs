@@ -0,0 +1,37 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// UpdateStatus : Progress of the current or previous software update or backend restart.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    /// Error message if the update failed.
+    #[serde(rename = "error", skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(rename = "state")]
+    pub state: models::UpdateState,
+    /// Completed and currently running steps in order.
+    #[serde(rename = "steps")]
+    pub steps: Vec<models::UpdateStepInfo>,
+}
+
+impl UpdateStatus {
+    /// Progress of the current or previous software update or backend restart.
+    pub fn new(state: models::UpdateState, steps: Vec<models::UpdateStepInfo>) -> UpdateStatus {
+        UpdateStatus {
+            error: None,
+            state,
+            steps,
+        }
+    }
+}
+
@@ -0,0 +1,59 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum UpdateStep {
+    #[serde(rename = "Download")]
+    Download,
+    #[serde(rename = "Install")]
+    Install,
+    #[serde(rename = "ResetData")]
+    ResetData,
+    #[serde(rename = "Reboot")]
+    Reboot,
+    #[serde(rename = "BackendDrain")]
+    BackendDrain,
+    #[serde(rename = "BackendStop")]
+    BackendStop,
+    #[serde(rename = "BackendKill")]
+    BackendKill,
+    #[serde(rename = "BackendStart")]
+    BackendStart,
+    #[serde(rename = "ManagerRestart")]
+    ManagerRestart,
+
+}
+
+impl std::fmt::Display for UpdateStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Download => write!(f, "Download"),
+            Self::Install => write!(f, "Install"),
+            Self::ResetData => write!(f, "ResetData"),
+            Self::Reboot => write!(f, "Reboot"),
+            Self::BackendDrain => write!(f, "BackendDrain"),
+            Self::BackendStop => write!(f, "BackendStop"),
+            Self::BackendKill => write!(f, "BackendKill"),
+            Self::BackendStart => write!(f, "BackendStart"),
+            Self::ManagerRestart => write!(f, "ManagerRestart"),
+        }
+    }
+}
+
+impl Default for UpdateStep {
+    fn default() -> UpdateStep {
+        Self::Download
+    }
+}
+
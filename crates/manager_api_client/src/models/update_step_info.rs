@@ -0,0 +1,34 @@
+/*
+ * app-manager
+ *
+ * App manager API
+ *
+ * The version of the OpenAPI document: 0.1.0
+ * 
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UpdateStepInfo {
+    /// Time from the update start to this step in milliseconds.
+    #[serde(rename = "elapsed_millis")]
+    pub elapsed_millis: i64,
+    #[serde(rename = "message")]
+    pub message: String,
+    #[serde(rename = "step")]
+    pub step: models::UpdateStep,
+}
+
+impl UpdateStepInfo {
+    pub fn new(elapsed_millis: i64, message: String, step: models::UpdateStep) -> UpdateStepInfo {
+        UpdateStepInfo {
+            elapsed_millis,
+            message,
+            step,
+        }
+    }
+}
+
@@ -91,6 +91,81 @@ pub struct BuildInfo {
     pub build_info: String,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[clap(rename_all = "snake_case")]
+pub enum SystemInfoSection {
+    /// Built-in commands like df and free.
+    Commands,
+    /// Commands from config file.
+    CustomCommands,
+    /// Journal logs and print-logs.sh output.
+    Logs,
+    Metrics,
+}
+
+impl SystemInfoSection {
+    pub const fn to_str(&self) -> &'static str {
+        match self {
+            Self::Commands => "commands",
+            Self::CustomCommands => "custom_commands",
+            Self::Logs => "logs",
+            Self::Metrics => "metrics",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct SystemInfoSectionsQueryParam {
+    /// Comma separated list of sections. Possible values are commands,
+    /// custom_commands, logs and metrics. All sections are returned
+    /// by default.
+    pub sections: Option<String>,
+}
+
+impl SystemInfoSectionsQueryParam {
+    pub fn new(sections: &[SystemInfoSection]) -> Self {
+        if sections.is_empty() {
+            return Self::default();
+        }
+        Self {
+            sections: Some(
+                sections
+                    .iter()
+                    .map(|s| s.to_str())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ),
+        }
+    }
+
+    /// Returns all sections if the parameter is missing.
+    pub fn sections(&self) -> Result<Vec<SystemInfoSection>, String> {
+        let Some(sections) = &self.sections else {
+            return Ok(SystemInfoSection::value_variants().to_vec());
+        };
+        sections
+            .split(',')
+            .map(|s| SystemInfoSection::from_str(s.trim(), true))
+            .collect()
+    }
+}
+
+/// Journal log stream parameters. Missing optional values are read from
+/// the service's config.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, IntoParams)]
+pub struct LogsQueryParam {
+    /// Service name from system info log_services config.
+    pub service: String,
+    /// Keep the response open and stream new log lines.
+    #[serde(default)]
+    pub follow: bool,
+    /// Journal priority filter. For example "warning" or "err..warning".
+    pub priority: Option<String>,
+    /// Show logs since this time. Uses journalctl time format.
+    pub since: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
 pub struct SystemInfoList {
     pub info: Vec<SystemInfo>,
@@ -100,10 +175,250 @@ pub struct SystemInfoList {
 pub struct SystemInfo {
     pub name: String,
     pub info: Vec<CommandOutput>,
+    /// Missing if the manager does not support system metrics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub metrics: Option<SystemMetrics>,
+    /// Error message if getting system info failed. For example remote
+    /// manager might not be available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub error: Option<String>,
+    /// Time used for getting the info in milliseconds. For remote
+    /// managers this includes the API request.
+    #[serde(default)]
+    pub duration_millis: u64,
+}
+
+/// System metrics in machine readable format.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub struct SystemMetrics {
+    pub disks: Vec<DiskUsage>,
+    pub memory: MemoryUsage,
+    pub load_average: LoadAverage,
+    pub uptime_seconds: u64,
+    pub cpu_count: u32,
+    /// Processes sorted by CPU usage.
+    pub top_processes: Vec<ProcessInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub struct DiskUsage {
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    /// Space available for unprivileged users.
+    pub available_bytes: u64,
+    pub total_inodes: u64,
+    pub used_inodes: u64,
+    pub available_inodes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub struct MemoryUsage {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    pub swap_total_bytes: u64,
+    pub swap_used_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub struct LoadAverage {
+    pub one_minute: f64,
+    pub five_minutes: f64,
+    pub fifteen_minutes: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// CPU usage during a short sample period. One fully used CPU
+    /// core is 100%.
+    pub cpu_percent: f64,
+    /// Resident set size
+    pub memory_bytes: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
 pub struct CommandOutput {
     pub name: String,
+    /// Standard output
     pub output: String,
+    #[serde(default)]
+    pub status: CommandStatus,
+    /// Missing if the command did not start or it was terminated by
+    /// a signal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub exit_code: Option<i32>,
+    /// Standard error or error message if the command did not start.
+    #[serde(default)]
+    pub stderr: String,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub enum CommandStatus {
+    #[default]
+    Success,
+    /// Command exited with non-zero exit code or it was terminated by
+    /// a signal.
+    Failed,
+    /// Command starting failed. For example the command does not exist.
+    StartFailed,
+    /// Command did not complete before timeout and it was killed.
+    Timeout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub struct ConfigReloadReport {
+    /// Config file fields which changed and are now in use.
+    pub reloaded: Vec<String>,
+    /// Config file fields which changed but require manager restart.
+    pub restart_required: Vec<String>,
+}
+
+/// Backend process status. Process related values are available only
+/// when the backend is supervised by the manager or controlled using
+/// systemd.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct BackendStatus {
+    /// Backend is started and stopped by the manager instead of scripts.
+    pub supervised: bool,
+    pub running: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub uptime_seconds: Option<u64>,
+    /// Exit code of the previous backend process. Missing if the process
+    /// has not exited or it was terminated by a signal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub last_exit_code: Option<i32>,
+    /// Restarts after unexpected backend exits.
+    pub restart_count: u32,
+    /// Backend is controlled using this systemd unit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub systemd_unit: Option<String>,
+    /// Systemd unit ActiveState. For example "active" or "failed".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub active_state: Option<String>,
+    /// Systemd unit SubState. For example "running" or "exited".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub sub_state: Option<String>,
+}
+
+/// Progress of the current or previous software update or backend
+/// restart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct UpdateStatus {
+    pub state: UpdateState,
+    /// Completed and currently running steps in order.
+    pub steps: Vec<UpdateStepInfo>,
+    /// Error message if the update failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, Default, ToSchema)]
+pub enum UpdateState {
+    /// No updates or backend restarts since the manager started.
+    #[default]
+    Idle,
+    InProgress,
+    Finished,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct UpdateStepInfo {
+    pub step: UpdateStep,
+    pub message: String,
+    /// Time from the update start to this step in milliseconds.
+    pub elapsed_millis: u64,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub enum UpdateStep {
+    Download,
+    Install,
+    ResetData,
+    Reboot,
+    /// Backend drain hook request.
+    BackendDrain,
+    /// Backend stop request. Supervised backend receives SIGTERM.
+    BackendStop,
+    /// Backend did not quit before the stop timeout and it was killed.
+    BackendKill,
+    BackendStart,
+    /// Manager is restarted using the new binary.
+    ManagerRestart,
+}
+
+/// Automatic reboot status.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct RebootStatus {
+    /// System will be rebooted at the next reboot check.
+    pub pending: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub reason: Option<RebootReason>,
+    /// Unix timestamp in seconds. Missing if automatic reboots are
+    /// disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub next_check_time: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub last_attempt: Option<RebootAttempt>,
+}
+
+/// Result of the cancel reboot request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct CancelRebootResult {
+    /// True if a reboot was pending and it is now cancelled.
+    pub was_pending: bool,
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub enum RebootReason {
+    /// File /var/run/reboot-required exists.
+    RebootRequiredFile,
+    SoftwareUpdate,
+    /// Reboot was scheduled using the manager API.
+    Scheduled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct RebootAttempt {
+    /// Unix timestamp in seconds.
+    pub time: i64,
+    pub success: bool,
+    /// Error message if the reboot failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(nullable = false)]
+    pub error: Option<String>,
+}
+
+/// Name of the server which requests or releases a reboot lease.
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, IntoParams)]
+pub struct RebootLeaseQueryParam {
+    pub name: String,
+}
+
+/// Reboot coordinator response to a reboot lease request.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct RebootLease {
+    /// Server can reboot now.
+    pub granted: bool,
+    /// Names of the servers which hold a reboot lease.
+    pub holders: Vec<String>,
 }